@@ -0,0 +1,74 @@
+//! benchmarks for the hot paths of the emulator (run with `cargo bench`)
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use chip8_core::chip8::Chip8;
+use chip8_core::quirks::Quirks;
+
+const ARITHMETIC_LOOP_INSTRUCTIONS: u64 = 100_000;
+
+/// executes every possible opcode once
+fn bench_decode(criterion: &mut Criterion) {
+    let mut chip8 = Chip8::new(Quirks::default());
+    let program_start_address = chip8.get_program_start_address();
+
+    let mut group = criterion.benchmark_group("decode");
+    group.throughput(Throughput::Elements(0x10000));
+    group.bench_function("all_opcodes", |bencher| bencher.iter(|| {
+        for opcode in 0x0000..=0xFFFF {
+            chip8.load_opcode_into_memory(opcode, program_start_address);
+            chip8.reset_state();
+
+            // unknown opcodes and stack errors are expected here
+            let _ = black_box(chip8.exec_next_instruction());
+        }
+    }));
+    group.finish();
+}
+
+/// runs an endless loop of arithmetic instructions
+fn bench_arithmetic_loop(criterion: &mut Criterion) {
+    let mut chip8 = Chip8::new(Quirks::default());
+
+    // V0 = 1; loop { V1 += 1; V0 += V1; }
+    let opcodes: Vec<u16> = vec!(0x6001, 0x7101, 0x8014, 0x1202);
+    chip8.load_opcodes_into_memory(&opcodes, chip8.get_program_start_address());
+
+    let mut group = criterion.benchmark_group("execute");
+    group.throughput(Throughput::Elements(ARITHMETIC_LOOP_INSTRUCTIONS));
+    group.bench_function("arithmetic_loop", |bencher| bencher.iter(|| {
+        chip8.reset_state();
+        chip8.run_instructions(black_box(ARITHMETIC_LOOP_INSTRUCTIONS)).expect("failed to run the arithmetic loop");
+    }));
+    group.finish();
+}
+
+/// covers the whole screen with 8x15 sprites
+fn bench_full_screen_draw(criterion: &mut Criterion) {
+    let mut chip8 = Chip8::new(Quirks::default());
+
+    chip8.load_bytes_into_memory(&[0xFF; 15], 0x300);
+
+    let mut opcodes: Vec<u16> = vec!(0xA300);
+    for y_pos in (0..32).step_by(15) {
+        for x_pos in (0..64).step_by(8) {
+            // V0 = x; V1 = y; draw
+            opcodes.push(0x6000 | x_pos);
+            opcodes.push(0x6100 | y_pos);
+            opcodes.push(0xD01F);
+        }
+    }
+    chip8.load_opcodes_into_memory(&opcodes, chip8.get_program_start_address());
+
+    let instruction_count = opcodes.len() as u64;
+
+    let mut group = criterion.benchmark_group("draw");
+    group.throughput(Throughput::Elements(instruction_count));
+    group.bench_function("full_screen", |bencher| bencher.iter(|| {
+        chip8.reset_state();
+        chip8.run_instructions(black_box(instruction_count)).expect("failed to draw the screen");
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode, bench_arithmetic_loop, bench_full_screen_draw);
+criterion_main!(benches);
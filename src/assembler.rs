@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use crate::chip8::DEFAULT_PROGRAM_START_ADDRESS;
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleError {
+    UnknownInstruction(String),
+    InvalidOperand(String),
+    UnknownLabel(String),
+    DuplicateLabel(String),
+}
+
+/// an operand of an instruction (e.g. `V3`, `0x200`, `[I]` or a label)
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Operand {
+    Register(u8),
+    Value(u16),
+    IndexReg,
+    IndexRegIndirect,
+    DelayTimer,
+    SoundTimer,
+    Key,
+    Font,
+    BigFont,
+    BinaryCodedDecimal,
+    RplFlags,
+}
+
+/// a single line of source that produces output
+struct Statement<'a> {
+    line_number: usize,
+    mnemonic: String,
+    operands: Vec<&'a str>,
+}
+
+/// assembles a program for the default program start address (see [`assemble_at()`])
+pub fn assemble(src: &str) -> Result<Vec<u8>, AssembleError> {
+    return assemble_at(src, DEFAULT_PROGRAM_START_ADDRESS);
+}
+
+/// assembles a program that is loaded at `origin` (needed for resolving labels)
+///
+/// the syntax is the one of the disassembler (e.g. `LD V0, 0x05`), extended by:
+///  - label definitions in the style of Octo (`: main`), which can be used instead of addresses (e.g. `JP main`)
+///  - data directives (`DB 0xFF, 0x81` for bytes and `DW 0x1234` for words)
+///  - comments starting with `#` or `;`
+///
+/// **NOTE:** mnemonics, registers and labels are case-insensitive
+pub fn assemble_at(src: &str, origin: u16) -> Result<Vec<u8>, AssembleError> {
+    let mut labels: HashMap<String, u16> = HashMap::new();
+    let mut statements: Vec<Statement> = vec!();
+
+    // first pass: collect the statements and the addresses of the labels
+    let mut address = origin;
+    for (line_idx, line) in src.lines().enumerate() {
+        let line_number = line_idx + 1;
+
+        // strip comments
+        let line = line.split(|c| c == '#' || c == ';').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(label) = line.strip_prefix(':') {
+            let label = label.trim().to_lowercase();
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return Err(AssembleError::InvalidOperand(format!("invalid label definition '{}' in line {}!", line, line_number)));
+            }
+            if labels.insert(label.clone(), address).is_some() {
+                return Err(AssembleError::DuplicateLabel(format!("the label '{}' in line {} is already defined!", label, line_number)));
+            }
+            continue;
+        }
+
+        let (mnemonic, operands) = match line.split_once(char::is_whitespace) {
+            Some((mnemonic, operands)) => (mnemonic, operands.split(',').map(|operand| operand.trim()).collect()),
+            None => (line, vec!()),
+        };
+
+        let statement = Statement { line_number, mnemonic: mnemonic.to_uppercase(), operands };
+        address = address.wrapping_add(get_statement_size(&statement) as u16);
+        statements.push(statement);
+    }
+
+    // second pass: encode the statements
+    let mut output: Vec<u8> = vec!();
+    for statement in statements.iter() {
+        let operands = statement.operands.iter()
+            .map(|operand| parse_operand(operand, &labels, statement.line_number))
+            .collect::<Result<Vec<Operand>, AssembleError>>()?;
+
+        match statement.mnemonic.as_str() {
+            "DB" => {
+                for operand in operands {
+                    output.push(expect_value(operand, 0xFF, statement)? as u8);
+                }
+            },
+            "DW" => {
+                for operand in operands {
+                    output.extend_from_slice(&expect_value(operand, 0xFFFF, statement)?.to_be_bytes());
+                }
+            },
+            _ => output.extend_from_slice(&encode_instruction(statement, &operands)?.to_be_bytes()),
+        }
+    }
+
+    return Ok(output);
+}
+
+/// returns the number of bytes the statement produces
+fn get_statement_size(statement: &Statement) -> usize {
+    return match statement.mnemonic.as_str() {
+        "DB" => statement.operands.len(),
+        "DW" => statement.operands.len() * 2,
+        _ => 2,
+    };
+}
+
+fn parse_operand(operand: &str, labels: &HashMap<String, u16>, line_number: usize) -> Result<Operand, AssembleError> {
+    let upper = operand.to_uppercase();
+
+    let keyword = match upper.as_str() {
+        "I" => Some(Operand::IndexReg),
+        "[I]" => Some(Operand::IndexRegIndirect),
+        "DT" => Some(Operand::DelayTimer),
+        "ST" => Some(Operand::SoundTimer),
+        "K" => Some(Operand::Key),
+        "F" => Some(Operand::Font),
+        "HF" => Some(Operand::BigFont),
+        "B" => Some(Operand::BinaryCodedDecimal),
+        "R" => Some(Operand::RplFlags),
+        _ => None,
+    };
+    if let Some(keyword) = keyword {
+        return Ok(keyword);
+    }
+
+    // registers (V0 - VF)
+    if upper.len() == 2 && upper.starts_with('V') {
+        if let Ok(reg_id) = u8::from_str_radix(&upper[1..], 16) {
+            return Ok(Operand::Register(reg_id));
+        }
+    }
+
+    // numbers (hexadecimal, binary or decimal)
+    let number = if let Some(hex) = upper.strip_prefix("0X") {
+        Some(u16::from_str_radix(hex, 16))
+    } else if let Some(binary) = upper.strip_prefix("0B") {
+        Some(u16::from_str_radix(binary, 2))
+    } else if upper.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(upper.parse::<u16>())
+    } else {
+        None
+    };
+    if let Some(number) = number {
+        return number
+            .map(|value| Operand::Value(value))
+            .map_err(|_| AssembleError::InvalidOperand(format!("invalid number '{}' in line {}!", operand, line_number)));
+    }
+
+    return match labels.get(&operand.to_lowercase()) {
+        Some(address) => Ok(Operand::Value(*address)),
+        None => Err(AssembleError::UnknownLabel(format!("the label '{}' in line {} is not defined!", operand, line_number))),
+    };
+}
+
+fn expect_value(operand: Operand, max: u16, statement: &Statement) -> Result<u16, AssembleError> {
+    return match operand {
+        Operand::Value(value) if value <= max => Ok(value),
+        _ => Err(AssembleError::InvalidOperand(format!("expected a value up to 0x{:X} in line {}!", max, statement.line_number))),
+    };
+}
+
+fn encode_instruction(statement: &Statement, operands: &[Operand]) -> Result<u16, AssembleError> {
+    use Operand::*;
+
+    let x_y = |x: u8, y: u8| ((x as u16) << 8) | ((y as u16) << 4);
+    let x_nn = |x: u8, nn: u16| ((x as u16) << 8) | nn;
+
+    let opcode = match (statement.mnemonic.as_str(), operands) {
+        ("HALT", []) => 0x0000,
+        ("CLS", []) => 0x00E0,
+        ("SCD", [rows]) => 0x00C0 | expect_value(*rows, 0xF, statement)?,
+        ("RET", []) => 0x00EE,
+        ("JP", [address]) => 0x1000 | expect_value(*address, 0xFFF, statement)?,
+        ("JP", [Register(0x0), address]) => 0xB000 | expect_value(*address, 0xFFF, statement)?,
+        ("CALL", [address]) => 0x2000 | expect_value(*address, 0xFFF, statement)?,
+        ("SE", [Register(x), Register(y)]) => 0x5000 | x_y(*x, *y),
+        ("SE", [Register(x), value]) => 0x3000 | x_nn(*x, expect_value(*value, 0xFF, statement)?),
+        ("SNE", [Register(x), Register(y)]) => 0x9000 | x_y(*x, *y),
+        ("SNE", [Register(x), value]) => 0x4000 | x_nn(*x, expect_value(*value, 0xFF, statement)?),
+        ("LD", [Register(x), Register(y)]) => 0x8000 | x_y(*x, *y),
+        ("LD", [Register(x), DelayTimer]) => 0xF007 | x_y(*x, 0),
+        ("LD", [Register(x), Key]) => 0xF00A | x_y(*x, 0),
+        ("LD", [Register(x), IndexRegIndirect]) => 0xF065 | x_y(*x, 0),
+        ("LD", [Register(x), RplFlags]) => 0xF085 | x_y(*x, 0),
+        ("LD", [Register(x), value]) => 0x6000 | x_nn(*x, expect_value(*value, 0xFF, statement)?),
+        ("LD", [IndexReg, address]) => 0xA000 | expect_value(*address, 0xFFF, statement)?,
+        ("LD", [DelayTimer, Register(x)]) => 0xF015 | x_y(*x, 0),
+        ("LD", [SoundTimer, Register(x)]) => 0xF018 | x_y(*x, 0),
+        ("LD", [Font, Register(x)]) => 0xF029 | x_y(*x, 0),
+        ("LD", [BigFont, Register(x)]) => 0xF030 | x_y(*x, 0),
+        ("LD", [BinaryCodedDecimal, Register(x)]) => 0xF033 | x_y(*x, 0),
+        ("LD", [IndexRegIndirect, Register(x)]) => 0xF055 | x_y(*x, 0),
+        ("LD", [RplFlags, Register(x)]) => 0xF075 | x_y(*x, 0),
+        ("ADD", [Register(x), Register(y)]) => 0x8004 | x_y(*x, *y),
+        ("ADD", [Register(x), value]) => 0x7000 | x_nn(*x, expect_value(*value, 0xFF, statement)?),
+        ("ADD", [IndexReg, Register(x)]) => 0xF01E | x_y(*x, 0),
+        ("OR", [Register(x), Register(y)]) => 0x8001 | x_y(*x, *y),
+        ("AND", [Register(x), Register(y)]) => 0x8002 | x_y(*x, *y),
+        ("XOR", [Register(x), Register(y)]) => 0x8003 | x_y(*x, *y),
+        ("SUB", [Register(x), Register(y)]) => 0x8005 | x_y(*x, *y),
+        ("SHR", [Register(x), Register(y)]) => 0x8006 | x_y(*x, *y),
+        ("SUBN", [Register(x), Register(y)]) => 0x8007 | x_y(*x, *y),
+        ("SHL", [Register(x), Register(y)]) => 0x800E | x_y(*x, *y),
+        ("RND", [Register(x), value]) => 0xC000 | x_nn(*x, expect_value(*value, 0xFF, statement)?),
+        ("DRW", [Register(x), Register(y), height]) => 0xD000 | x_y(*x, *y) | expect_value(*height, 0xF, statement)?,
+        ("SKP", [Register(x)]) => 0xE09E | x_y(*x, 0),
+        ("SKNP", [Register(x)]) => 0xE0A1 | x_y(*x, 0),
+        ("PLANE", [planes]) => 0xF001 | (expect_value(*planes, 0xF, statement)? << 8),
+        ("AUDIO", []) => 0xF002,
+        ("PITCH", [Register(x)]) => 0xF03A | x_y(*x, 0),
+        ("HALT" | "CLS" | "SCD" | "RET" | "JP" | "CALL" | "SE" | "SNE" | "LD" | "ADD" | "OR" | "AND" | "XOR" | "SUB" | "SHR" | "SUBN" | "SHL" | "RND" | "DRW" | "SKP" | "SKNP" | "PLANE" | "AUDIO" | "PITCH", _) => {
+            return Err(AssembleError::InvalidOperand(format!("invalid operands for '{}' in line {}!", statement.mnemonic, statement.line_number)));
+        },
+        _ => return Err(AssembleError::UnknownInstruction(format!("unknown instruction '{}' in line {}!", statement.mnemonic, statement.line_number))),
+    };
+
+    return Ok(opcode);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip8::Chip8;
+    use crate::disassembler::disassemble;
+    use crate::quirks::Quirks;
+
+    #[test]
+    fn assemble_and_run_program() {
+        let src = "
+            # counts V0 up to 5 in a subroutine
+            : main
+                LD V0, 0
+            : loop
+                CALL increment
+                SE V0, 5
+                JP loop
+                LD I, sprite
+                DRW V1, V1, 2
+                HALT
+
+            : increment ; adds 1 to V0
+                ADD V0, 1
+                RET
+
+            : sprite
+                DB 0b11111111, 0x81
+        ";
+
+        let program_data = assemble(src).expect("failed to assemble the program");
+
+        let mut chip8 = Chip8::new(Quirks::default());
+        chip8.load_program(&program_data).expect("failed to load the program");
+        while !chip8.is_halted() {
+            chip8.exec_next_instruction().expect("failed to execute the program");
+        }
+
+        // verify result
+        assert_eq!(&program_data[0..4], &[0x60, 0x00, 0x22, 0x0E], "failed to correctly resolve the label");
+        assert_eq!(chip8.get_register(0), 5, "failed to correctly run the assembled program; V0: {}", chip8.get_register(0));
+        assert!(chip8.frame().get_pixel(0, 0) && chip8.frame().get_pixel(7, 1), "failed to correctly draw the assembled sprite");
+    }
+
+    #[test]
+    fn assemble_disassembled_opcodes() {
+        for opcode in 0x0000..=0xFFFF {
+            let mnemonic = disassemble(opcode);
+            let program_data = assemble(&mnemonic).expect("failed to assemble the mnemonic");
+
+            assert_eq!(program_data, opcode.to_be_bytes().to_vec(), "failed to assemble '{}' into 0x{:04X}", mnemonic, opcode);
+        }
+    }
+
+    #[test]
+    fn assemble_invalid_programs() {
+        assert!(matches!(assemble("JP nowhere"), Err(AssembleError::UnknownLabel(_))), "failed to reject the unknown label");
+        assert!(matches!(assemble(": a\n: A"), Err(AssembleError::DuplicateLabel(_))), "failed to reject the duplicate label");
+        assert!(matches!(assemble("LD V0, 0x100"), Err(AssembleError::InvalidOperand(_))), "failed to reject the too large value");
+        assert!(matches!(assemble("DRW V0, V1"), Err(AssembleError::InvalidOperand(_))), "failed to reject the missing operand");
+        assert!(matches!(assemble("JUMP 0x200"), Err(AssembleError::UnknownInstruction(_))), "failed to reject the unknown instruction");
+    }
+}
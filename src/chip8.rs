@@ -1,13 +1,34 @@
-extern crate rand;
-
-use std::time::{Duration, Instant};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::fs::{self, File};
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+#[cfg(feature = "std")]
+use std::path::Path;
+use crate::disassembler;
 use crate::keypad::Keypad;
+use crate::quirks::{self, IndexOnDumpLoad, Quirks, UnknownOpcodePolicy};
+#[cfg(feature = "std")]
+use crate::save_state::{SaveState, SaveStateError};
 use crate::screen;
 use crate::stack::Stack;
-use crate::screen::Screen;
-use rand::thread_rng;
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use crate::screen::{CollisionMode, FrameBuffer, Screen, ScreenSnapshot, SpriteDrawMode};
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+use log::{debug, info, trace, warn};
+use serde::{Serialize, Deserialize};
+use rand::{Rng, SeedableRng};
+use rand::rngs::SmallRng;
+
+/// target of the log messages of the emulator core (e.g. `RUST_LOG=chip8=trace` shows every executed instruction)
+const LOG_TARGET: &str = "chip8";
 
 /// specifies the ID of the VF register which is often used for flags
 const FLAG_REG_ID: u8 = 0xF;
@@ -15,37 +36,264 @@ const FLAG_REG_ID: u8 = 0xF;
 /// specifies the address where the fonts data is stored in memory
 const FONT_START_ADDRESS: u16 = 0x050;
 
-/// specifies the address where the program is stored in memory
-pub const PROGRAM_START_ADDRESS: u16 = 0x200;
+/// specifies the address where the program is stored in memory by most interpreters
+pub const DEFAULT_PROGRAM_START_ADDRESS: u16 = 0x200;
+
+/// specifies the address where the program is stored in memory by the ETI 660 interpreter
+pub const ETI_660_PROGRAM_START_ADDRESS: u16 = 0x600;
+
+/// memory size of the original CHIP-8 (4KB)
+pub const DEFAULT_MEMORY_SIZE: usize = 0x1000;
+
+/// memory size of XO-CHIP (64KB), which is also the most a 16 bit address can reach
+pub const XO_CHIP_MEMORY_SIZE: usize = 0x10000;
 
 const INSTRUCTION_EXEC_DURATION: Duration = Duration::from_nanos(1_428_571); // 1_428_571
 
+/// costs of the instructions per opcode group (multiples of `INSTRUCTION_EXEC_DURATION`) if every instruction takes the
+/// same time
+pub const UNIFORM_INSTRUCTION_COSTS: [u32; 16] = [1; 16];
+
+/// relative costs of the instructions per opcode group (multiples of `INSTRUCTION_EXEC_DURATION`), which roughly
+/// approximate the COSMAC VIP (draws, BCD and the subroutine handling are a lot slower than the arithmetic)
+pub const CYCLE_ACCURATE_COSTS: [u32; 16] = [
+    3, // 0x0: CLS, RET
+    1, // 0x1: JP
+    2, // 0x2: CALL
+    1, // 0x3: SE
+    1, // 0x4: SNE
+    1, // 0x5: SE
+    1, // 0x6: LD
+    1, // 0x7: ADD
+    1, // 0x8: arithmetic
+    1, // 0x9: SNE
+    1, // 0xA: LD I
+    1, // 0xB: JP V0
+    1, // 0xC: RND
+    8, // 0xD: DRW
+    1, // 0xE: SKP, SKNP
+    3, // 0xF: timers, BCD, register dump/load
+];
+
+/// duration of a single frame at 60 FPS (used when there is no real time to measure)
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// the first bytes of every gzip file
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// number of RPL flags (SUPER-CHIP)
+pub const RPL_FLAG_COUNT: usize = 8;
+
+/// size of the XO-CHIP audio pattern in bytes (128 bits, played back from the most significant bit of the first byte)
+pub const AUDIO_PATTERN_SIZE: usize = 16;
+
+/// XO-CHIP pitch at which the audio pattern is played back at 4000 bits per second
+pub const DEFAULT_PITCH: u8 = 64;
+
+/// number of executed instructions that are kept for post-mortem debugging if no other capacity is set
+pub const DEFAULT_RECENT_INSTRUCTIONS_CAPACITY: usize = 64;
+
+/// number of instructions a single frame may execute if no other cap is set (far above any real program's needs)
+pub const DEFAULT_MAX_INSTRUCTIONS_PER_FRAME: u64 = 100_000;
+
+/// number of instructions `step_over()` runs at most before giving up on the subroutine returning (minutes of emulated
+/// time at the default clock speed)
+pub const STEP_OVER_MAX_INSTRUCTIONS: u64 = 100_000;
+
 const FONT_DATA_SIZE: usize = 80; // 5 rows per char * 16 chars
 
+/// number of bytes (rows) per glyph of the font (`FX29`)
+const FONT_GLYPH_SIZE: u16 = 5;
+
+/// specifies the address where the big font (SUPER-CHIP) is stored in memory (right after the font)
+const BIG_FONT_START_ADDRESS: u16 = FONT_START_ADDRESS + FONT_DATA_SIZE as u16;
+
+const BIG_FONT_DATA_SIZE: usize = 100; // 10 rows per char * 10 chars (digits only)
+
+/// number of bytes (rows) per glyph of the big font (`FX30`)
+const BIG_FONT_GLYPH_SIZE: u16 = 10;
+
+/// the big font that is installed if no other big font is loaded (the digits of SUPER-CHIP 1.1)
+const DEFAULT_BIG_FONT_DATA: [u8; BIG_FONT_DATA_SIZE] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+];
+
+/// the font that is installed if no other font is loaded
+const DEFAULT_FONT_DATA: [u8; FONT_DATA_SIZE] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+
 #[derive(Debug)]
 pub enum Chip8Error {
-    InstructionNotImplemented(String),
+    /// the instruction at `address` has no implementation (see [`UnknownOpcodePolicy`])
+    UnknownOpcode { opcode: u16, address: u16 },
     MemoryOverflow(String),
+    MemoryOutOfBounds(String),
+    RomTooLarge(String),
+    #[cfg(feature = "std")]
+    IO(io::Error),
     InvalidFontData(String),
+    InvalidHexString(String),
+    StackOverflow(String),
+    StackUnderflow(String),
+    InvalidRegister(String),
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Chip8Error::UnknownOpcode { opcode, address } => write!(f, "there is no implementation for the instruction 0x{:04x} that was found at mem address 0x{:04x}!", opcode, address),
+            #[cfg(feature = "std")]
+            Chip8Error::IO(err) => write!(f, "{}", err),
+            Chip8Error::MemoryOverflow(message)
+            | Chip8Error::MemoryOutOfBounds(message)
+            | Chip8Error::RomTooLarge(message)
+            | Chip8Error::InvalidFontData(message)
+            | Chip8Error::InvalidHexString(message)
+            | Chip8Error::StackOverflow(message)
+            | Chip8Error::StackUnderflow(message)
+            | Chip8Error::InvalidRegister(message) => write!(f, "{}", message),
+        };
+    }
+}
+
+/// specifies why the emulator stopped running instructions
+///
+/// **NOTE:** running stops at the first condition, so only one of them is reported; they are checked in this order:
+///  1. `InstructionLimitReached` and `BreakpointHit` (before the instruction is executed)
+///  2. `WatchpointHit` (after the instruction is executed)
+///  3. `ReachedEndOfFile` and `Halted` (after the instruction is executed)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RunOutcome {
+    /// the emulator can keep running
+    Continue,
+
+    /// the emulator is halted by the program (see [`Chip8::is_halted()`])
+    Halted,
+
+    /// the emulator is halted because the program counter ran past the end of memory
+    ReachedEndOfFile,
+
+    /// the program counter reached a breakpoint (see [`Chip8::add_breakpoint()`]); the instruction at this address is
+    /// executed when running again
+    BreakpointHit(u16),
+
+    /// the program wrote to a watched address (see [`Chip8::add_watchpoint()`])
+    WatchpointHit(u16),
+
+    /// the instruction limit was reached (see [`Chip8::set_max_instructions()`])
+    InstructionLimitReached,
+}
+
+/// specifies how many instructions `run_frame()` executes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PacingMode {
+    /// the number of instructions depends on the passed frame duration (and the fixed clock speed)
+    ClockSpeed,
+
+    /// exactly this many instructions are executed per frame, regardless of the frame duration
+    CyclesPerFrame(u32),
+}
+
+/// everything a frontend needs after a call to [`Chip8::tick()`]
+#[derive(Debug)]
+pub struct TickResult<'a> {
+    /// the frame buffer after the tick (ready to be drawn)
+    pub frame: &'a FrameBuffer,
+
+    /// whether the buzzer should currently be sounding
+    pub beep: bool,
+
+    pub outcome: RunOutcome,
+}
+
+/// a write to the flag register VF by an instruction (see [`Chip8::set_flag_hook()`])
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FlagChange {
+    /// address of the instruction that wrote the flag
+    pub address: u16,
+
+    pub opcode: u16,
+
+    pub old_value: u8,
+
+    pub new_value: u8,
+}
+
+/// called on every flag write (e.g. for logging them while debugging)
+pub type FlagHook = Box<dyn FnMut(FlagChange)>;
+
+/// the value the memory is filled with outside of the fonts and the program (see [`Chip8::set_memory_pattern()`])
+///
+/// **NOTE:** real interpreters left garbage in the memory, so some programs accidentally depend on it
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum MemoryPattern {
+    Zeros,
+
+    /// every byte is `0xFF`
+    Ones,
+
+    /// every byte has the given value
+    Byte(u8),
+}
+
+impl MemoryPattern {
+    pub fn byte(&self) -> u8 {
+        return match self {
+            MemoryPattern::Zeros => 0x00,
+            MemoryPattern::Ones => 0xFF,
+            MemoryPattern::Byte(byte) => *byte,
+        };
+    }
 }
 
+impl Default for MemoryPattern {
+    fn default() -> Self {
+        return MemoryPattern::Zeros;
+    }
+}
+
+/// seed of the random numbers of `CXNN` until the frontend passes in its own one (see [`Chip8::seed_random_generator()`])
+const DEFAULT_RANDOM_SEED: u64 = 0xC8C8_C8C8;
+
 pub struct Chip8 {
     registers: [u8; 16],
 
     // position in memory
     program_counter: u16,
 
-    // 4096 bytes of memory
-    memory: [u8; 0x1000],
+    // 4096 bytes of memory by default (64KB for XO-CHIP)
+    memory: Vec<u8>,
 
-    /// specifies if the Y register is loaded into X before doing bit-shift operations or not
-    assign_before_shift: bool,
+    /// where the program is loaded and executed from (`0x200` by default)
+    program_start_address: u16,
 
-    /// specifies whether it sets VF to 1 if I overflows from 0FFF to above 0x1000 (outside the normal addressing space) or not
-    set_flag_on_index_overflow: bool,
-
-    /// specifies if I is incremented during the FX55 (reg_dump) and FX65 (reg_load) instructions
-    modify_index_on_dump_or_load: bool,
+    quirks: Quirks,
 
     stack: Stack,
 
@@ -56,45 +304,188 @@ pub struct Chip8 {
     /// aka. the I register (used to point at locations in memory)
     index_reg: u16,
 
+    /// the user flags of the HP 48 calculators (`FX75`/`FX85`), which survive a reset
+    rpl_flags: [u8; RPL_FLAG_COUNT],
+
     delay_timer: u8,
 
     sound_timer: u8,
 
     playing_sound: bool,
 
+    /// the XO-CHIP audio pattern (`F002`), which replaces the beep once a program loads one
+    audio_pattern: Option<[u8; AUDIO_PATTERN_SIZE]>,
+
+    /// the XO-CHIP playback rate of the audio pattern (`FX3A`, see [`Chip8::get_audio_pattern_rate()`])
+    pitch: u8,
+
     exec_time: Duration,
 
-    last_exec: Instant,
+    pacing_mode: PacingMode,
 
+    /// costs of the instructions per opcode group (multiples of `INSTRUCTION_EXEC_DURATION`), which `run_frame()` and
+    /// `advance()` subtract from the time budget
+    instruction_costs: [u32; 16],
+
+    /// time that passed in `advance()` but wasn't used for decrementing the timers yet (less than a frame)
+    timer_time: Duration,
+
+    /// set if the program counter ran past the end of memory
     reached_end_of_file: bool,
 
-    random_generator: ThreadRng,
+    /// number of instructions that have been executed so far
+    instructions_executed: u64,
+
+    /// number of frames run so far (calls to `run_frame()` and full 1/60 seconds in `advance()`)
+    frames_run: u64,
+
+    /// total time passed to `run_frame()` and `advance()` so far (the measured wall time when driven by a frontend)
+    time_elapsed: Duration,
+
+    /// ring buffer of the last executed instructions as `(address, opcode)` (oldest first)
+    recent_instructions: VecDeque<(u16, u16)>,
+
+    /// maximum number of entries in `recent_instructions` (0 disables the recording)
+    recent_instructions_capacity: usize,
+
+    /// stops running instructions once `instructions_executed` reaches this limit (used against infinite loops)
+    max_instructions: Option<u64>,
+
+    /// maximum number of instructions per call of `run_frame()` or `advance()` (protects against catching up after a stall)
+    max_instructions_per_frame: u64,
+
+    /// addresses at which running stops before the instruction is executed
+    breakpoints: BTreeSet<u16>,
+
+    /// addresses at which running stops only if a register holds a value (`address -> (register ID, value)`)
+    conditional_breakpoints: BTreeMap<u16, (u8, u8)>,
+
+    /// the breakpoint that was hit last; it's skipped once, so running again continues past it
+    resumed_breakpoint: Option<u16>,
+
+    /// memory addresses at which running stops after they are written to
+    watchpoints: BTreeSet<u16>,
+
+    /// the watched address that was written by the last instruction
+    watchpoint_hit: Option<u16>,
+
+    /// warn about writes into the loaded program (self-modifying code, which is usually a bug)
+    detect_code_writes: bool,
+
+    /// the address of the last write into the loaded program (only tracked if `detect_code_writes` is set)
+    last_code_write: Option<u16>,
+
+    /// the keypad state of every frame since `start_recording()` (`None` = not recording)
+    recording: Option<Vec<Keypad>>,
+
+    /// the instruction that is currently executed as `(address, opcode)` (reported to the flag hook)
+    current_instruction: (u16, u16),
+
+    /// called on every write of the flag register VF (see [`Chip8::set_flag_hook()`])
+    flag_hook: Option<FlagHook>,
+
+    /// set on an intentional halt (`0x0000` instruction) or at the end of memory; no further instructions are executed
+    halted: bool,
+
+    /// source of the random numbers of `CXNN`; seeded, so runs can be reproduced
+    random_generator: SmallRng,
+
+    /// the currently installed font (restored on reset)
+    font_data: Vec<u8>,
+
+    /// the currently installed big font (restored on reset)
+    big_font_data: Vec<u8>,
+
+    /// the currently loaded program (restored on reset)
+    rom_data: Vec<u8>,
+
+    /// identifies the currently loaded program (see [`quirks::rom_checksum()`])
+    rom_checksum: u32,
+
+    /// the value of the memory outside of the fonts and the program (applied on reset)
+    memory_pattern: MemoryPattern,
 }
 
 impl Chip8 {
-    pub fn new(assign_before_shift: bool, set_flag_on_index_overflow: bool, modify_index_on_dump_or_load: bool) -> Self {
-        return Chip8 {
+    /// creates an emulator with the default memory layout
+    ///
+    /// **NOTE:** the emulator never reads the real clock, the time only advances through the durations passed to
+    /// `advance()`, `run_frame()` and `tick()` (so tests are driven by explicit calls)
+    pub fn new(quirks: Quirks) -> Self {
+        return Self::with_memory_size(DEFAULT_MEMORY_SIZE, quirks);
+    }
+
+    /// **NOTE:** panics if `memory_size` can not hold a program or exceeds the 16 bit address space ([`XO_CHIP_MEMORY_SIZE`])
+    pub fn with_memory_size(memory_size: usize, quirks: Quirks) -> Self {
+        return Self::with_memory_layout(memory_size, DEFAULT_PROGRAM_START_ADDRESS, quirks);
+    }
+
+    /// creates an emulator for variants which load programs at another address (e.g. [`ETI_660_PROGRAM_START_ADDRESS`])
+    ///
+    /// **NOTE:** panics if `memory_size` can not hold a program or exceeds the 16 bit address space ([`XO_CHIP_MEMORY_SIZE`])
+    pub fn with_memory_layout(memory_size: usize, program_start_address: u16, quirks: Quirks) -> Self {
+        if memory_size <= (program_start_address as usize) || memory_size > XO_CHIP_MEMORY_SIZE {
+            panic!("invalid memory size 0x{:x} for a program start at 0x{:04x}!", memory_size, program_start_address);
+        }
+
+        let mut chip8 = Chip8 {
             registers: [0; 16],
-            program_counter: PROGRAM_START_ADDRESS,
-            memory: [0; 0x1000],
-            assign_before_shift,
-            set_flag_on_index_overflow,
-            modify_index_on_dump_or_load,
-            stack: Stack::new(),
+            program_counter: program_start_address,
+            memory: vec![0; memory_size],
+            program_start_address,
+            quirks,
+            stack: Stack::default(),
             screen: Screen::new(),
             keypad: Keypad::new(),
             index_reg: 0x0,
+            rpl_flags: [0; RPL_FLAG_COUNT],
             sound_timer: 0,
             delay_timer: 0,
             playing_sound: false,
+            audio_pattern: None,
+            pitch: DEFAULT_PITCH,
             exec_time: Duration::new(0, 0),
-            last_exec: Instant::now(),
+            pacing_mode: PacingMode::ClockSpeed,
+            instruction_costs: UNIFORM_INSTRUCTION_COSTS,
+            timer_time: Duration::new(0, 0),
             reached_end_of_file: false,
-            random_generator: thread_rng(),
+            instructions_executed: 0,
+            frames_run: 0,
+            time_elapsed: Duration::new(0, 0),
+            recent_instructions: VecDeque::with_capacity(DEFAULT_RECENT_INSTRUCTIONS_CAPACITY),
+            recent_instructions_capacity: DEFAULT_RECENT_INSTRUCTIONS_CAPACITY,
+            breakpoints: BTreeSet::new(),
+            conditional_breakpoints: BTreeMap::new(),
+            resumed_breakpoint: None,
+            watchpoints: BTreeSet::new(),
+            watchpoint_hit: None,
+            detect_code_writes: false,
+            last_code_write: None,
+            recording: None,
+            current_instruction: (0, 0),
+            flag_hook: None,
+            max_instructions: None,
+            max_instructions_per_frame: DEFAULT_MAX_INSTRUCTIONS_PER_FRAME,
+            halted: false,
+            random_generator: SmallRng::seed_from_u64(DEFAULT_RANDOM_SEED),
+            font_data: DEFAULT_FONT_DATA.to_vec(),
+            big_font_data: DEFAULT_BIG_FONT_DATA.to_vec(),
+            rom_data: vec!(),
+            rom_checksum: quirks::rom_checksum(&[]),
+            memory_pattern: MemoryPattern::default(),
         };
+
+        chip8.load_bytes_into_memory(&DEFAULT_FONT_DATA, FONT_START_ADDRESS);
+        chip8.load_bytes_into_memory(&DEFAULT_BIG_FONT_DATA, BIG_FONT_START_ADDRESS);
+        chip8.screen.set_sprite_wrap(quirks.sprite_wrap);
+        chip8.screen.set_lores_scroll_half(quirks.lores_scroll_half);
+
+        return chip8;
     }
 
     /// **NOTE:** in comparison to the `add_const_to_x()` method, this one **does** set a carry flag, thus affecting the VF register
+    ///
+    /// like all the flag setting operations, VF is written last, so it holds the flag even if X is VF (e.g. `8FY4`)
     fn add_y_to_x(&mut self, x_reg_id: u8, y_reg_id: u8) {
         let arg_1 = self.registers[x_reg_id as usize];
         let arg_2 = self.registers[y_reg_id as usize];
@@ -103,7 +494,20 @@ impl Chip8 {
         self.registers[x_reg_id as usize] = value;
 
         // set carry flag
-        self.registers[FLAG_REG_ID as usize] = if carry { 1 } else { 0 };
+        self.set_flag(if carry { 1 } else { 0 });
+    }
+
+    /// writes the flag register VF (every flag that an instruction sets goes through here, so the flag hook sees all of them)
+    ///
+    /// **NOTE:** instructions that merely use VF as their target register (e.g. `6FNN`) don't count as flag writes
+    fn set_flag(&mut self, value: u8) {
+        let old_value = self.registers[FLAG_REG_ID as usize];
+        self.registers[FLAG_REG_ID as usize] = value;
+
+        let (address, opcode) = self.current_instruction;
+        if let Some(flag_hook) = self.flag_hook.as_mut() {
+            flag_hook(FlagChange { address, opcode, old_value, new_value: value });
+        }
     }
 
     /// **NOTE:** in comparison to the `add_y_to_x()` method, this one **does not** set a carry flag, thus not affecting the VF register
@@ -124,7 +528,7 @@ impl Chip8 {
         self.registers[x_reg_id as usize] = value;
 
         // set underflow flag
-        self.registers[FLAG_REG_ID as usize] = if underflow { 0 } else { 1 };
+        self.set_flag(if underflow { 0 } else { 1 });
     }
 
     /// - **NOTE_1:** even though the method subtracts **`x`** from **`y`**, the result is still stored in **`x`**
@@ -138,7 +542,7 @@ impl Chip8 {
         self.registers[x_reg_id as usize] = value;
 
         // set underflow flag
-        self.registers[FLAG_REG_ID as usize] = if underflow { 0 } else { 1 };
+        self.set_flag(if underflow { 0 } else { 1 });
     }
 
     fn assign_const_to_x(&mut self, x_reg_id: u8, const_val: u8) {
@@ -163,43 +567,47 @@ impl Chip8 {
 
     /// shifts the X register 1 position to the right
     ///  - VF is set to the value of the least-significant-bit before the shift operation
-    ///  - the `assign_before_shift` bool, which can be configured on creation, specifies whether the Y register is loaded into the X register before doing the shift operation
+    ///  - the `assign_before_shift` quirk specifies whether the Y register is loaded into the X register before doing the shift operation
     fn right_bit_shift(&mut self, x_reg_id: u8, y_reg_id: u8) {
-        if self.assign_before_shift {
+        if self.quirks.assign_before_shift {
             self.assign_y_to_x(x_reg_id, y_reg_id);
         }
 
-        // set VF to LSB
-        self.registers[FLAG_REG_ID as usize] = self.registers[x_reg_id as usize] & (0x01 as u8);
-
+        // take the LSB before shifting (the shift overwrites it if X is VF)
+        let shifted_out_bit = self.registers[x_reg_id as usize] & (0x01 as u8);
         self.registers[x_reg_id as usize] >>= 1;
+
+        // set VF last, so that it holds the flag (and not the result) if X is VF
+        self.set_flag(shifted_out_bit);
     }
 
     /// shifts the X register 1 position to the left
     ///  - VF is set to the value of the most-significant-bit before the shift operation
-    ///  - the `assign_before_shift` bool, which can be configured on creation, specifies whether the Y register is loaded into the X register before doing the shift operation
+    ///  - the `assign_before_shift` quirk specifies whether the Y register is loaded into the X register before doing the shift operation
     fn left_bit_shift(&mut self, x_reg_id: u8, y_reg_id: u8) {
-        if self.assign_before_shift {
+        if self.quirks.assign_before_shift {
             self.assign_y_to_x(x_reg_id, y_reg_id);
         }
 
-        // set VF to LSB
-        self.registers[FLAG_REG_ID as usize] = (self.registers[x_reg_id as usize] & (0x80 as u8)) >> 7;
-
+        // take the MSB before shifting (the shift overwrites it if X is VF)
+        let shifted_out_bit = (self.registers[x_reg_id as usize] & (0x80 as u8)) >> 7;
         self.registers[x_reg_id as usize] <<= 1;
+
+        // set VF last, so that it holds the flag (and not the result) if X is VF
+        self.set_flag(shifted_out_bit);
     }
 
     fn skip_if_x_equals_const(&mut self, x_reg_id: u8, const_val: u8) {
         let x_reg = &self.registers[x_reg_id as usize];
         if *x_reg == const_val {
-            self.program_counter += 2;
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
 
     fn skip_if_x_not_equals_const(&mut self, x_reg_id: u8, const_val: u8) {
         let x_reg = &self.registers[x_reg_id as usize];
         if *x_reg != const_val {
-            self.program_counter += 2;
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
 
@@ -207,96 +615,161 @@ impl Chip8 {
         let x_reg = &self.registers[x_reg_id as usize];
         let y_reg = &self.registers[y_reg_id as usize];
         if *x_reg == *y_reg {
-            self.program_counter += 2;
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
     fn skip_if_x_not_equals_y(&mut self, x_reg_id: u8, y_reg_id: u8) {
         let x_reg = &self.registers[x_reg_id as usize];
         let y_reg = &self.registers[y_reg_id as usize];
         if *x_reg != *y_reg {
-            self.program_counter += 2;
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
 
-    fn call_subroutine(&mut self, address: u16) {
-        self.stack.push(self.program_counter);
+    fn call_subroutine(&mut self, address: u16) -> Result<(), Chip8Error> {
+        self.stack.push(self.program_counter)
+            .map_err(|_| Chip8Error::StackOverflow(format!("failed to call the subroutine at 0x{:04x}, because the stack is full", address)))?;
         self.program_counter = address;
+
+        return Ok(());
     }
 
-    fn return_from_subroutine(&mut self) {
-        let address: u16 = self.stack.pop();
+    fn return_from_subroutine(&mut self) -> Result<(), Chip8Error> {
+        let address: u16 = self.stack.pop()
+            .ok_or(Chip8Error::StackUnderflow(String::from("failed to return from the subroutine, because the stack is empty")))?;
         self.program_counter = address;
+
+        return Ok(());
     }
 
     fn jump_to_address(&mut self, address: u16) {
         self.program_counter = address;
     }
 
-    /// jumps to V0 + address
+    /// jumps to V0 + address (wraps around the end of memory)
     fn jump_to_address_with_displacement(&mut self, address: u16) {
-        self.jump_to_address(address + (self.registers[0x0] as u16));
+        let target_address = self.wrap_address(address.wrapping_add(self.registers[0x0] as u16) as usize);
+        self.jump_to_address(target_address as u16);
     }
 
     fn set_index_reg(&mut self, address: u16) {
         self.index_reg = address;
     }
 
-    /// **NOTE:** if the `set_flag_on_index_overflow` bool is set to `true`,
-    /// then in case of the index register moving outside the normal addressing range (`0x1000`), VF is set to `1`
+    /// **NOTE:** if the `set_flag_on_index_overflow` quirk is set to `true`,
+    /// then in case of the index register moving outside the addressable memory (`0x1000` by default), VF is set to `1`
     fn add_x_to_index(&mut self, x_reg_id: u8) {
-        self.index_reg += self.registers[x_reg_id as usize] as u16;
+        self.index_reg = self.index_reg.wrapping_add(self.registers[x_reg_id as usize] as u16);
 
         // set overflow flag
-        if self.set_flag_on_index_overflow && self.index_reg > 0x1000 {
-            self.registers[FLAG_REG_ID as usize] = 1;
+        if self.quirks.set_flag_on_index_overflow && (self.index_reg as usize) >= self.memory.len() {
+            self.set_flag(1);
         }
     }
 
     fn set_index_to_char_font(&mut self, x_reg_id: u8) {
         // reduce to the least significant nibble
         let character = self.registers[x_reg_id as usize] & 0xF;
-        self.index_reg = FONT_START_ADDRESS + (character as u16) * 5;
+        self.index_reg = FONT_START_ADDRESS + (character as u16) * FONT_GLYPH_SIZE;
+    }
+
+    /// points I to the big font glyph of the digit in VX (SUPER-CHIP `FX30`)
+    ///
+    /// **NOTE:** the big font only contains the digits 0-9, so A-F point past its end (like on SUPER-CHIP 1.1)
+    fn set_index_to_char_big_font(&mut self, x_reg_id: u8) {
+        // reduce to the least significant nibble
+        let character = self.registers[x_reg_id as usize] & 0xF;
+        self.index_reg = BIG_FONT_START_ADDRESS + (character as u16) * BIG_FONT_GLYPH_SIZE;
     }
 
     fn dump_registers_to_memory(&mut self, x_reg_id: u8) {
         let mut address: u16 = self.index_reg;
         for idx in 0..(x_reg_id + 1) {
-            self.memory[address as usize] = self.registers[idx as usize];
-            address += 1;
+            let mem_address = self.wrap_address(address as usize);
+            self.write_memory(mem_address, self.registers[idx as usize]);
+            address = address.wrapping_add(1);
         }
 
-        if self.modify_index_on_dump_or_load {
-            self.index_reg = address;
-        }
+        self.modify_index_after_dump_or_load(x_reg_id);
     }
 
     fn load_registers_from_memory(&mut self, x_reg_id: u8) {
         let mut address: u16 = self.index_reg;
         for idx in 0..(x_reg_id + 1) {
-            self.registers[idx as usize] = self.memory[address as usize];
-            address += 1;
+            self.registers[idx as usize] = self.memory[self.wrap_address(address as usize)];
+            address = address.wrapping_add(1);
         }
 
-        if self.modify_index_on_dump_or_load {
-            self.index_reg = address;
-        }
+        self.modify_index_after_dump_or_load(x_reg_id);
+    }
+
+    /// stores V0 to VX in the RPL flags (SUPER-CHIP)
+    ///
+    /// **NOTE:** there are only 8 flags, so registers above V7 are ignored
+    fn store_registers_in_rpl_flags(&mut self, x_reg_id: u8) {
+        let count = (x_reg_id as usize + 1).min(RPL_FLAG_COUNT);
+        self.rpl_flags[..count].copy_from_slice(&self.registers[..count]);
+    }
+
+    /// loads V0 to VX from the RPL flags (SUPER-CHIP)
+    ///
+    /// **NOTE:** there are only 8 flags, so registers above V7 are left unchanged
+    fn load_registers_from_rpl_flags(&mut self, x_reg_id: u8) {
+        let count = (x_reg_id as usize + 1).min(RPL_FLAG_COUNT);
+        self.registers[..count].copy_from_slice(&self.rpl_flags[..count]);
     }
 
-    fn display_sprite(&mut self, x_reg_id: u8, y_reg_id: u8, pixel_height: u8) {
+    /// applies the `index_on_dump_or_load` quirk after V0 to VX were dumped to or loaded from memory
+    fn modify_index_after_dump_or_load(&mut self, x_reg_id: u8) {
+        self.index_reg = match self.quirks.index_on_dump_or_load {
+            IndexOnDumpLoad::PlusXPlus1 => self.index_reg.wrapping_add(x_reg_id as u16 + 1),
+            IndexOnDumpLoad::PlusX => self.index_reg.wrapping_add(x_reg_id as u16),
+            IndexOnDumpLoad::Unchanged => self.index_reg,
+        };
+    }
+
+    /// **NOTE:** if both planes are selected (XO-CHIP), the sprite data of the second plane follows the one of the first
+    /// plane, so twice as many bytes are read
+    fn display_sprite(&mut self, x_reg_id: u8, y_reg_id: u8, pixel_height: u8) -> Result<(), Chip8Error> {
         let x_pos = self.registers[x_reg_id as usize];
         let y_pos = self.registers[y_reg_id as usize];
 
-        let sprite_data = &self.memory[(self.index_reg as usize)..(self.index_reg as usize) + (pixel_height as usize)];
+        let plane_count = self.screen.get_selected_planes().count_ones() as u16;
+        let sprite_data: Vec<u8> = self.read_sprite_bytes(pixel_height as u16 * plane_count)?.to_vec();
+
+        let collision = self.screen.display_sprite(x_pos, y_pos, &sprite_data);
+        self.set_flag(collision);
+        return Ok(());
+    }
 
-        if self.screen.display_sprite(x_pos, y_pos, sprite_data) {
-            self.registers[FLAG_REG_ID as usize] = 1;
+    /// returns the `height` bytes of sprite data starting at the index register (every sprite read goes through this)
+    ///
+    /// **NOTE:** returns a [`Chip8Error::MemoryOutOfBounds`] if the sprite data would exceed the memory
+    fn read_sprite_bytes(&self, height: u16) -> Result<&[u8], Chip8Error> {
+        let start = self.index_reg as usize;
+        let end = start + height as usize;
+
+        if end > self.memory.len() {
+            return Err(Chip8Error::MemoryOutOfBounds(format!("the sprite data at mem address 0x{:04x} with a height of {} exceeds the memory", start, height)));
         }
+
+        return Ok(&self.memory[start..end]);
     }
 
     fn clear_screen(&mut self) {
         self.screen.clear();
     }
 
+    /// scrolls the screen down by `amount` rows (SUPER-CHIP `00CN`, see [`Screen::scroll_down()`])
+    fn scroll_down(&mut self, amount: u8) {
+        self.screen.scroll_down(amount);
+    }
+
+    /// selects the planes that the following draws and clears affect (XO-CHIP `FN01`, see [`Screen::select_planes()`])
+    fn select_planes(&mut self, planes: u8) {
+        self.screen.select_planes(planes);
+    }
+
     fn set_x_to_delay_timer(&mut self, x_red_id: u8) {
         self.registers[x_red_id as usize] = self.delay_timer;
     }
@@ -309,28 +782,48 @@ impl Chip8 {
         self.sound_timer = self.registers[x_reg_id as usize];
     }
 
+    /// loads the 16 bytes at I into the audio pattern (XO-CHIP `F002`)
+    fn load_audio_pattern(&mut self) {
+        let mut audio_pattern = [0; AUDIO_PATTERN_SIZE];
+        for (offset, byte) in audio_pattern.iter_mut().enumerate() {
+            *byte = self.memory[self.wrap_address((self.index_reg as usize) + offset)];
+        }
+
+        self.audio_pattern = Some(audio_pattern);
+    }
+
+    /// sets the playback rate of the audio pattern (XO-CHIP `FX3A`)
+    fn set_pitch_to_x(&mut self, x_reg_id: u8) {
+        self.pitch = self.registers[x_reg_id as usize];
+    }
+
+    /// **NOTE:** a key that was tapped during the frame counts as pressed once (see [`Keypad::latch_pressed()`])
     fn skip_if_key_pressed(&mut self, x_reg_id: u8) {
-        let key_id: u8 = self.registers[x_reg_id as usize];
-        if self.keypad.check_key_state(key_id) {
-            self.program_counter += 2;
+        // reduce to the least significant nibble (there are only 16 keys)
+        let key_id: u8 = self.registers[x_reg_id as usize] & 0xF;
+        if self.keypad.latch_pressed(key_id) {
+            self.keypad.consume_latch(key_id);
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
 
+    /// **NOTE:** a key that was tapped during the frame counts as pressed until the tap is consumed by `EX9E` or `FX0A`
     fn skip_if_key_not_pressed(&mut self, x_reg_id: u8) {
-        let key_id: u8 = self.registers[x_reg_id as usize];
-        if !self.keypad.check_key_state(key_id) {
-            self.program_counter += 2;
+        // reduce to the least significant nibble (there are only 16 keys)
+        let key_id: u8 = self.registers[x_reg_id as usize] & 0xF;
+        if !self.keypad.latch_pressed(key_id) {
+            self.program_counter = self.program_counter.wrapping_add(2);
         }
     }
 
     fn await_keypress(&mut self, x_reg_id: u8) {
-        let keypress: Option<u8> = self.keypad.get_keypress();
+        let keypress: Option<u8> = self.keypad.take_latched_keypress();
 
         if let Some(key_id) = keypress {
             self.registers[x_reg_id as usize] = key_id;
         } else {
             // repeat instruction until keypress is found
-            self.program_counter -= 2;
+            self.program_counter = self.program_counter.wrapping_sub(2);
         }
     }
 
@@ -352,12 +845,35 @@ impl Chip8 {
         let ones: u8 = x_val;
 
         // store parts
-        self.memory[self.index_reg as usize] = hundreds;
-        self.memory[(self.index_reg as usize) + 1] = tens;
-        self.memory[(self.index_reg as usize) + 2] = ones;
+        for (offset, digit) in [hundreds, tens, ones].iter().enumerate() {
+            let mem_address = self.wrap_address((self.index_reg as usize) + offset);
+            self.write_memory(mem_address, *digit);
+        }
+    }
+
+    /// writes a byte from within an instruction (which can trigger a watchpoint)
+    fn write_memory(&mut self, address: usize, value: u8) {
+        self.memory[address] = value;
+
+        if self.watchpoints.contains(&(address as u16)) {
+            self.watchpoint_hit = Some(address as u16);
+        }
+
+        let program_start = self.program_start_address as usize;
+        if self.detect_code_writes && address >= program_start && address < program_start + self.rom_data.len() {
+            warn!(target: LOG_TARGET, "code region write: 0x{:04X} was written by the instruction at 0x{:04X}", address, self.program_counter.wrapping_sub(2));
+            self.last_code_write = Some(address as u16);
+        }
+    }
+
+    /// wraps addresses that point past the end of memory back around to the start
+    fn wrap_address(&self, address: usize) -> usize {
+        return address % self.memory.len();
     }
 
-    fn fetch_instruction(&mut self) -> u16 {
+    /// **NOTE:** the program counter doesn't have to be aligned (jumps to odd addresses are legal) and the instruction is
+    /// always read from the current memory, so self-modifying code works as expected
+    fn fetch_instruction(&self) -> u16 {
         // all opcodes are 2 bytes long and stored in big-endian format
         /*
         big-endian:
@@ -365,19 +881,55 @@ impl Chip8 {
             - least significant byte -> largest memory address
          */
 
-        let byte_1 = self.memory[self.program_counter as usize] as u8; // most significant byte
-        let byte_2 = self.memory[(self.program_counter + 1) as usize] as u8; // least significant byte
+        let byte_1 = self.memory[self.wrap_address(self.program_counter as usize)]; // most significant byte
+        let byte_2 = self.memory[self.wrap_address((self.program_counter as usize) + 1)]; // least significant byte
 
         return ((byte_1 as u16) << 8) | (byte_2 as u16);
     }
 
-    /// returns `false` if there was nothing to execute (empty instruction)
-    pub fn exec_next_instruction(&mut self) -> Result<(), Chip8Error> {
+    /// returns the address and opcode of the instruction that is executed next, without changing any state (e.g. for
+    /// showing it in a debugger)
+    ///
+    /// **NOTE:** returns `None` if there is no complete instruction left in memory (executing would halt the emulator)
+    pub fn peek_next_instruction(&self) -> Option<(u16, u16)> {
+        if (self.program_counter as usize) + 1 >= self.memory.len() {
+            return None;
+        }
+
+        return Some((self.program_counter, self.fetch_instruction()));
+    }
+
+    /// returns the opcode at the program counter without executing it
+    ///
+    /// **NOTE:** returns `0x0000` (the halt instruction) if there is no complete instruction left in memory
+    pub fn peek_opcode(&self) -> u16 {
+        return self.peek_next_instruction().map_or(0x0000, |(_, opcode)| opcode);
+    }
+
+    /// executes the instruction at the program counter (does nothing if the emulator is halted)
+    ///  - returns `true` if the emulator is still running afterwards and `false` if it is halted
+    ///
+    /// **NOTE:** this never panics; unknown instructions and invalid states (e.g. a stack overflow) are reported as [`Chip8Error`]
+    pub fn exec_next_instruction(&mut self) -> Result<bool, Chip8Error> {
+        if self.halted {
+            return Ok(false);
+        }
+
+        // halt if there is no complete instruction left in memory
+        if (self.program_counter as usize) + 1 >= self.memory.len() {
+            self.reached_end_of_file = true;
+            self.halted = true;
+            return Ok(false);
+        }
+
         let opcode = self.fetch_instruction();
-        self.program_counter += 2;
+        trace!(target: LOG_TARGET, "0x{:04X}: {:04X} {}", self.program_counter, opcode, disassembler::disassemble(opcode));
+        self.record_instruction(self.program_counter, opcode);
+        self.current_instruction = (self.program_counter, opcode);
+        self.program_counter = self.program_counter.wrapping_add(2);
+        self.instructions_executed += 1;
+        self.resumed_breakpoint = None;
 
-        // println!("time elapsed since last exec: {:?}; instruction: {:04x}", self.last_exec.elapsed(), opcode);
-        self.last_exec = Instant::now();
 
         // opcode group (4 bit) -> first nibble
         let opcode_group: u8 = ((opcode & 0xF000) >> 12) as u8;
@@ -401,8 +953,8 @@ impl Chip8 {
         let nibble_const_val: u8 = (opcode & 0x000F) as u8;
 
         match (opcode_group, x_reg_id, y_reg_id, opcode_subgroup) {
-            // stop execution on empty instruction
-            (0x0, 0x0, 0x0, 0x0) => self.reached_end_of_file = true,
+            // halt on empty instruction (also covers running into uninitialized memory)
+            (0x0, 0x0, 0x0, 0x0) => self.halted = true,
 
             // basic math
             (0x8, _, _, 0x4) => self.add_y_to_x(x_reg_id, y_reg_id),
@@ -426,27 +978,36 @@ impl Chip8 {
             (0x9, _, _, 0x0) => self.skip_if_x_not_equals_y(x_reg_id, y_reg_id),
 
             // flow-control
-            (0x0, 0x0, 0xE, 0xE) => self.return_from_subroutine(),
+            (0x0, 0x0, 0xE, 0xE) => self.return_from_subroutine()?,
             (0x1, _, _, _) => self.jump_to_address(address),
-            (0x2, _, _, _) => self.call_subroutine(address),
+            (0x2, _, _, _) => self.call_subroutine(address)?,
             (0xB, _, _, _) => self.jump_to_address_with_displacement(address),
 
             // memory control
             (0xA, _, _, _) => self.set_index_reg(address),
             (0xF, _, 0x1, 0xE) => self.add_x_to_index(x_reg_id),
             (0xF, _, 0x2, 0x9) => self.set_index_to_char_font(x_reg_id),
+            (0xF, _, 0x3, 0x0) => self.set_index_to_char_big_font(x_reg_id),
             (0xF, _, 0x5, 0x5) => self.dump_registers_to_memory(x_reg_id),
             (0xF, _, 0x6, 0x5) => self.load_registers_from_memory(x_reg_id),
+            (0xF, _, 0x7, 0x5) => self.store_registers_in_rpl_flags(x_reg_id),
+            (0xF, _, 0x8, 0x5) => self.load_registers_from_rpl_flags(x_reg_id),
 
             // display
-            (0xD, _, _, _) => self.display_sprite(x_reg_id, y_reg_id, nibble_const_val),
+            (0xD, _, _, _) => self.display_sprite(x_reg_id, y_reg_id, nibble_const_val)?,
             (0x0, 0x0, 0xE, 0x0) => self.clear_screen(),
+            (0x0, 0x0, 0xC, _) => self.scroll_down(nibble_const_val),
+            (0xF, _, 0x0, 0x1) => self.select_planes(x_reg_id),
 
             // timers
             (0xF, _, 0x0, 0x7) => self.set_x_to_delay_timer(x_reg_id),
             (0xF, _, 0x1, 0x5) => self.set_delay_timer_to_x(x_reg_id),
             (0xF, _, 0x1, 0x8) => self.set_sound_timer_to_x(x_reg_id),
 
+            // audio (XO-CHIP)
+            (0xF, 0x0, 0x0, 0x2) => self.load_audio_pattern(),
+            (0xF, _, 0x3, 0xA) => self.set_pitch_to_x(x_reg_id),
+
             // key input
             (0xE, _, 0x9, 0xE) => self.skip_if_key_pressed(x_reg_id),
             (0xE, _, 0xA, 0x1) => self.skip_if_key_not_pressed(x_reg_id),
@@ -458,674 +1019,3120 @@ impl Chip8 {
             // binary-coded decimal
             (0xF, _, 0x3, 0x3) => self.store_binary_coded_decimal_of_x(x_reg_id),
 
-            _ => return Err(Chip8Error::InstructionNotImplemented(String::from(format!("there is no implementation for the instruction 0x{:04x} that was found at mem address 0x{:04x}!", opcode, self.program_counter - 2))))
+            _ => self.handle_unknown_opcode(opcode)?,
+        }
+
+        return Ok(!self.halted);
+    }
+
+    /// applies the `unknown_opcode_policy` quirk (the program counter already points to the next instruction)
+    fn handle_unknown_opcode(&mut self, opcode: u16) -> Result<(), Chip8Error> {
+        let address = self.program_counter.wrapping_sub(2);
+
+        match self.quirks.unknown_opcode_policy {
+            UnknownOpcodePolicy::Error => return Err(Chip8Error::UnknownOpcode { opcode, address }),
+            UnknownOpcodePolicy::Ignore => debug!(target: LOG_TARGET, "ignored the unknown instruction 0x{:04x} at 0x{:04x}", opcode, address),
+            UnknownOpcodePolicy::Halt => {
+                info!(target: LOG_TARGET, "halted on the unknown instruction 0x{:04x} at 0x{:04x}", opcode, address);
+                self.halted = true;
+            },
         }
 
         return Ok(());
     }
 
-    pub fn run_frame(&mut self, frame_duration: Duration) -> Result<(), Chip8Error> {
+    /// **NOTE:** `frame_duration` is ignored if a fixed number of cycles per frame is set (see [`PacingMode`])
+    pub fn run_frame(&mut self, frame_duration: Duration) -> Result<RunOutcome, Chip8Error> {
+        self.frames_run += 1;
+        self.time_elapsed += frame_duration;
+
         // update timers
         self.decrement_timers();
 
+        if let PacingMode::CyclesPerFrame(cycles) = self.pacing_mode {
+            return self.run_instructions((cycles as u64).min(self.max_instructions_per_frame));
+        }
+
         self.exec_time += frame_duration;
+        return self.run_exec_time();
+    }
+
+    /// advances the emulator by the time that passed (e.g. the measured time since the last frame)
+    ///  - the timers are decremented once for every full 1/60 of a second (leftover time is carried over)
+    ///  - the number of executed instructions is derived from `elapsed` as well (see [`PacingMode`])
+    ///
+    /// **NOTE:** the emulator never reads the real clock, so the same durations always lead to the same state
+    pub fn advance(&mut self, elapsed: Duration) -> Result<RunOutcome, Chip8Error> {
+        self.timer_time += elapsed;
+        self.time_elapsed += elapsed;
+
+        let mut frames: u32 = 0;
+        while self.timer_time >= FRAME_DURATION {
+            self.timer_time -= FRAME_DURATION;
+            self.frames_run += 1;
+            self.decrement_timers();
+            frames += 1;
+        }
+
+        if let PacingMode::CyclesPerFrame(cycles) = self.pacing_mode {
+            return self.run_instructions(((cycles as u64) * (frames as u64)).min(self.max_instructions_per_frame));
+        }
+
+        self.exec_time += elapsed;
+        return self.run_exec_time();
+    }
+
+    /// runs instructions as long as `exec_time` lasts (the remaining time is carried over to the next call)
+    ///
+    /// **NOTE:** once `max_instructions_per_frame` is reached, the remaining time is discarded, so the emulator continues
+    /// at normal speed after a stall instead of trying to catch up
+    fn run_exec_time(&mut self) -> Result<RunOutcome, Chip8Error> {
+        let mut instructions: u64 = 0;
+
+        while !self.halted {
+            let cost = self.get_instruction_cost(self.fetch_instruction());
+            if self.exec_time < cost {
+                break;
+            }
+
+            if instructions >= self.max_instructions_per_frame {
+                self.exec_time = Duration::new(0, 0);
+                break;
+            }
+            instructions += 1;
+
+            if let Some(outcome) = self.check_before_instruction() {
+                return Ok(outcome);
+            }
 
-        // run instructions
-        while self.exec_time >= INSTRUCTION_EXEC_DURATION && !self.reached_end_of_file {
             self.exec_next_instruction()?;
-            self.exec_time -= INSTRUCTION_EXEC_DURATION;
+            self.exec_time -= cost;
+
+            if let Some(outcome) = self.check_after_instruction() {
+                return Ok(outcome);
+            }
         }
 
-        return Ok(());
+        return Ok(self.get_run_outcome());
     }
 
-    pub fn load_keypad(&mut self, keypad: &Keypad) {
-        self.keypad = keypad.clone();
+    /// advances the emulator by one frame: loads the keypad, runs the emulator for `dt` and reports the outputs
+    ///
+    /// **NOTE:** `dt` is the real time that passed since the last tick (see `advance()`)
+    pub fn tick(&mut self, keypad: Keypad, dt: Duration) -> Result<TickResult<'_>, Chip8Error> {
+        self.load_keypad(&keypad);
+
+        let outcome = self.advance(dt)?;
+
+        return Ok(TickResult {
+            frame: self.screen.get_frame_buffer(),
+            beep: self.playing_sound,
+            outcome,
+        });
     }
 
-    /// **NOTE:** should be executed 60 times a second -> every frame
-    fn decrement_timers(&mut self) {
-        // decrement delay timer
-        self.delay_timer -= if self.delay_timer >= 1 { 1 } else { 0 };
+    /// loads the program and runs it for up to `max_frames` frames without any frontend, then returns the final frame
+    ///  - `keypad_source` is called with the frame index and returns the keypad state for that frame
+    ///  - stops early on any outcome other than [`RunOutcome::Continue`] (e.g. a halt or a breakpoint)
+    ///
+    /// **NOTE:** every frame is assumed to take exactly 1/60 of a second
+    pub fn load_and_run<F: FnMut(usize) -> Keypad>(&mut self, rom: &[u8], max_frames: usize, mut keypad_source: F) -> Result<FrameBuffer, Chip8Error> {
+        self.load_program(rom)?;
 
-        // decrement sound timer
-        if self.sound_timer <= 1 {
-            self.playing_sound = false;
-            self.sound_timer = 0;
-        } else {
-            self.playing_sound = true;
-            self.sound_timer -= 1;
+        for frame_idx in 0..max_frames {
+            let result = self.tick(keypad_source(frame_idx), FRAME_DURATION)?;
+
+            if result.outcome != RunOutcome::Continue {
+                break;
+            }
         }
+
+        return Ok(self.frame().clone());
     }
 
-    pub fn load_bytes_into_memory(&mut self, data: &Vec<u8>, address: u16) {
-        for (offset, byte) in data.iter().enumerate() {
-            self.memory[(address as usize) + offset] = *byte;
+    /// runs up to `count` instructions, independent of any timing
+    pub fn run_instructions(&mut self, count: u64) -> Result<RunOutcome, Chip8Error> {
+        for _ in 0..count {
+            if self.halted {
+                break;
+            }
+
+            if let Some(outcome) = self.check_before_instruction() {
+                return Ok(outcome);
+            }
+
+            self.exec_next_instruction()?;
+
+            if let Some(outcome) = self.check_after_instruction() {
+                return Ok(outcome);
+            }
         }
-    }
 
-    pub fn load_opcode_into_memory(&mut self, opcode: u16, address: u16) {
-        let byte_1 = ((opcode & 0xFF00) >> 8) as u8;
-        let byte_2 = (opcode & 0x00FF) as u8;
+        return Ok(self.get_run_outcome());
+    }
 
-        self.memory[address as usize] = byte_1;
-        self.memory[(address + 1) as usize] = byte_2;
+    /// executes exactly one instruction (e.g. for stepping through a program)
+    pub fn step(&mut self) -> Result<RunOutcome, Chip8Error> {
+        return self.run_instructions(1);
     }
 
-    pub fn load_opcodes_into_memory(&mut self, opcodes: &Vec<u16>, mut address: u16) {
-        for opcode in opcodes {
-            self.load_opcode_into_memory(*opcode, address);
-            address += 2;
+    /// like `step()`, but runs a called subroutine (`2NNN`) up to its return instead of stepping into it
+    ///  - a temporary breakpoint is set at the instruction after the call (it's only reported if it was set before)
+    ///  - recursive calls that pass the return address are run through until the stack is back at the original depth
+    ///
+    /// **NOTE:** a subroutine that never returns is run until the instruction limit is reached (see [`Chip8::set_max_instructions()`])
+    /// or [`STEP_OVER_MAX_INSTRUCTIONS`] were executed (the emulator then stops inside the subroutine)
+    pub fn step_over(&mut self) -> Result<RunOutcome, Chip8Error> {
+        match self.peek_next_instruction() {
+            Some((_, opcode)) if opcode & 0xF000 == 0x2000 => {},
+            _ => return self.step(),
         }
-    }
 
-    pub fn load_program(&mut self, program_data: &Vec<u8>) -> Result<(), Chip8Error> {
-        if program_data.len() > (self.memory.len() - (PROGRAM_START_ADDRESS as usize)) {
-            return Err(Chip8Error::MemoryOverflow(String::from("the program does not fit into its predefined memory space")));
+        let return_address = self.program_counter.wrapping_add(2);
+        let stack_pointer = self.stack.stack_pointer;
+        let temporary_breakpoint = self.breakpoints.insert(return_address);
+
+        let start_instructions = self.instructions_executed;
+        let mut result = self.step();
+        while let Ok(outcome) = &result {
+            let in_subroutine = *outcome == RunOutcome::Continue
+                || (temporary_breakpoint && *outcome == RunOutcome::BreakpointHit(return_address) && self.stack.stack_pointer != stack_pointer);
+            let executed = self.instructions_executed - start_instructions;
+            if !in_subroutine || executed >= STEP_OVER_MAX_INSTRUCTIONS {
+                break;
+            }
+
+            result = self.run_instructions(STEP_OVER_MAX_INSTRUCTIONS - executed);
         }
 
-        self.load_bytes_into_memory(program_data, PROGRAM_START_ADDRESS);
+        // remove the temporary breakpoint even if an error occurred
+        if temporary_breakpoint {
+            self.breakpoints.remove(&return_address);
+        }
 
-        return Ok(());
+        let mut outcome = result?;
+        if temporary_breakpoint && outcome == RunOutcome::BreakpointHit(return_address) {
+            outcome = self.get_run_outcome();
+        }
+
+        return Ok(outcome);
     }
 
-    pub fn load_font(&mut self, font_data: &Vec<u8>) -> Result<(), Chip8Error> {
-        if font_data.len() != FONT_DATA_SIZE {
-            return Err(Chip8Error::InvalidFontData(String::from("the fonts data does not fit into its predefined memory space")));
-        }
+    /// makes `run_frame()` execute exactly `cycles` instructions per call (e.g. 11 for the COSMAC VIP or 30 for SUPER-CHIP)
+    pub fn set_cycles_per_frame(&mut self, cycles: u32) {
+        self.set_pacing_mode(PacingMode::CyclesPerFrame(cycles));
+    }
 
-        self.load_bytes_into_memory(font_data, FONT_START_ADDRESS);
+    pub fn set_pacing_mode(&mut self, pacing_mode: PacingMode) {
+        self.pacing_mode = pacing_mode;
+        self.exec_time = Duration::new(0, 0);
+    }
 
-        return Ok(());
+    pub fn get_pacing_mode(&self) -> PacingMode {
+        return self.pacing_mode;
     }
 
-    pub fn load_register(&mut self, reg_id: u8, value: u8) {
-        self.registers[reg_id as usize] = value;
+    /// if enabled, `run_frame()` charges slow instructions (e.g. draws) more time than fast ones (e.g. additions)
+    ///  - shorthand for setting the instruction costs to `CYCLE_ACCURATE_COSTS` or `UNIFORM_INSTRUCTION_COSTS`
+    pub fn set_cycle_accurate(&mut self, cycle_accurate: bool) {
+        self.instruction_costs = if cycle_accurate { CYCLE_ACCURATE_COSTS } else { UNIFORM_INSTRUCTION_COSTS };
     }
 
-    pub fn load_index_reg(&mut self, address: u16) {
-        self.set_index_reg(address);
+    pub fn is_cycle_accurate(&self) -> bool {
+        return self.instruction_costs == CYCLE_ACCURATE_COSTS;
     }
 
-    pub fn load_registers(&mut self, values: &[u8; 16]) {
-        for (reg_id, value) in values.iter().enumerate() {
-            self.registers[reg_id] = *value;
-        }
+    /// sets the cost of the instructions per opcode group (index = highest nibble of the opcode) in multiples of the
+    /// uniform instruction duration
+    ///
+    /// **NOTE:** an instruction with a cost of 0 doesn't consume any time, so only `max_instructions_per_frame` ends a
+    /// frame that loops over it
+    pub fn set_instruction_costs(&mut self, instruction_costs: [u32; 16]) {
+        self.instruction_costs = instruction_costs;
     }
 
-    pub fn playing_sound(&self) -> bool {
-        return self.playing_sound;
+    pub fn get_instruction_costs(&self) -> [u32; 16] {
+        return self.instruction_costs;
     }
 
-    pub fn reached_end_of_file(&self) -> bool {
-        return self.reached_end_of_file;
+    /// returns how much of the time budget of `run_frame()` the instruction consumes
+    fn get_instruction_cost(&self, opcode: u16) -> Duration {
+        let opcode_group = ((opcode & 0xF000) >> 12) as usize;
+        return INSTRUCTION_EXEC_DURATION * self.instruction_costs[opcode_group];
     }
 
-    pub fn reset_state(&mut self) {
-        self.reached_end_of_file = false;
-        self.program_counter = PROGRAM_START_ADDRESS;
-        for val in self.registers.iter_mut() {
-            *val = 0;
-        }
+    /// limits the total number of instructions that `run_frame()` and `run_instructions()` execute (`None` = no limit)
+    pub fn set_max_instructions(&mut self, max_instructions: Option<u64>) {
+        self.max_instructions = max_instructions;
     }
 
-    pub fn get_frame_buffer(&self) -> &[[bool; (screen::WIDTH as usize)]; (screen::HEIGHT as usize)] {
-        return self.screen.get_frame_buffer();
+    /// limits the number of instructions a single `run_frame()` or `advance()` call executes (e.g. after the application
+    /// was suspended, the passed duration would otherwise be caught up all at once)
+    pub fn set_max_instructions_per_frame(&mut self, max_instructions_per_frame: u64) {
+        self.max_instructions_per_frame = max_instructions_per_frame;
     }
 
-    pub fn print_debug_info(&self) {
-        println!("==== CHIP-8 CPU DEBUG INFO (START) ====");
+    fn instruction_limit_reached(&self) -> bool {
+        return match self.max_instructions {
+            Some(max_instructions) => self.instructions_executed >= max_instructions,
+            None => false,
+        };
+    }
 
-        // output registers
-        println!("REGISTERS:");
-        for (i, reg) in self.registers.iter().enumerate() {
-            println!("\t{:02}: 0x{:04x} = {:3}", i, reg, reg);
+    /// stops running if the instruction limit or a breakpoint is reached
+    fn check_before_instruction(&mut self) -> Option<RunOutcome> {
+        if self.instruction_limit_reached() {
+            return Some(RunOutcome::InstructionLimitReached);
         }
 
-        println!("==== CHIP-8 CPU DEBUG INFO (END) ====");
-    }
-}
+        let address = self.program_counter;
+        let condition_met = match self.conditional_breakpoints.get(&address) {
+            Some((reg_id, value)) => self.registers[*reg_id as usize] == *value,
+            None => false,
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        if (self.breakpoints.contains(&address) || condition_met) && self.resumed_breakpoint != Some(address) {
+            self.resumed_breakpoint = Some(address);
+            return Some(RunOutcome::BreakpointHit(address));
+        }
 
-    fn init_emulator() -> Chip8 {
-        let chip8 = Chip8::new(true, true, false);
+        return None;
+    }
 
-        return chip8;
+    /// stops running if the last instruction wrote to a watched address
+    fn check_after_instruction(&mut self) -> Option<RunOutcome> {
+        return self.watchpoint_hit.take().map(|address| RunOutcome::WatchpointHit(address));
     }
 
-    fn run_emulator(chip8: &mut Chip8) {
-        while !chip8.reached_end_of_file {
-            chip8.exec_next_instruction().expect("an error occurred during emulator execution");
-        }
+    fn get_run_outcome(&self) -> RunOutcome {
+        return if self.reached_end_of_file {
+            RunOutcome::ReachedEndOfFile
+        } else if self.halted {
+            RunOutcome::Halted
+        } else {
+            RunOutcome::Continue
+        };
     }
 
-    #[test]
-    fn add_xy() {
-        let mut chip8 = init_emulator();
+    /// sets how many of the last executed instructions are kept (0 disables the recording)
+    pub fn set_recent_instructions_capacity(&mut self, capacity: usize) {
+        self.recent_instructions_capacity = capacity;
 
-        let val_1 = 5;
-        let val_2 = 7;
+        while self.recent_instructions.len() > capacity {
+            self.recent_instructions.pop_front();
+        }
+    }
 
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+    /// returns the last executed instructions as `(address, opcode)` (oldest first), e.g. to see what led to an error
+    ///
+    /// **NOTE:** an instruction that failed is included as the last entry
+    pub fn recent_instructions(&self) -> Vec<(u16, u16)> {
+        return self.recent_instructions.iter().copied().collect();
+    }
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8014, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+    fn record_instruction(&mut self, address: u16, opcode: u16) {
+        if self.recent_instructions_capacity == 0 {
+            return;
+        }
 
-        // verify result
-        assert_eq!(chip8.registers[0], val_1 + val_2, "failed to correctly add the two registers; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        if self.recent_instructions.len() >= self.recent_instructions_capacity {
+            self.recent_instructions.pop_front();
+        }
+        self.recent_instructions.push_back((address, opcode));
+    }
 
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 0, "failed to correctly set the carry bit; VF register: 0x{:02x}", vf_register);
+    /// makes `run_frame()` and `run_instructions()` stop before executing the instruction at `address`
+    pub fn add_breakpoint(&mut self, address: u16) {
+        self.breakpoints.insert(address);
+    }
+
+    /// like `add_breakpoint()`, but only stops if the register holds `value` at that point (e.g. to stop in a loop once
+    /// the counter reaches a value)
+    ///  - replaces the condition of a previous conditional breakpoint at the same address
+    pub fn add_conditional_breakpoint(&mut self, address: u16, reg_id: u8, value: u8) {
+        check_reg_id(reg_id);
+        self.conditional_breakpoints.insert(address, (reg_id, value));
+    }
+
+    /// removes the breakpoint at `address` (including a conditional one)
+    pub fn remove_breakpoint(&mut self, address: u16) {
+        self.breakpoints.remove(&address);
+        self.conditional_breakpoints.remove(&address);
+    }
+
+    /// makes `run_frame()` and `run_instructions()` stop after an instruction wrote to `address`
+    ///
+    /// **NOTE:** only writes of the program itself are watched (e.g. `FX55`), not the ones of the `load_*()` methods
+    pub fn add_watchpoint(&mut self, address: u16) {
+        self.watchpoints.insert(address);
+    }
+
+    pub fn remove_watchpoint(&mut self, address: u16) {
+        self.watchpoints.remove(&address);
+    }
+
+    /// makes the emulator warn (in the log) whenever the program writes into its own code, which is usually a bug
+    ///  - the code is the loaded program (from the program start address up to its length)
+    ///  - the address of the last such write is available via `last_code_write()`
+    ///
+    /// **NOTE:** some programs modify their code on purpose, so this is disabled by default
+    pub fn set_detect_code_writes(&mut self, detect_code_writes: bool) {
+        self.detect_code_writes = detect_code_writes;
+    }
+
+    /// returns the address of the last write of the program into its own code (see `set_detect_code_writes()`)
+    pub fn last_code_write(&self) -> Option<u16> {
+        return self.last_code_write;
+    }
+
+    /// installs a hook that is called whenever an instruction sets the flag register VF (carry, borrow, shifted out bit,
+    /// index overflow and sprite collision), with the old and the new value (`None` removes it)
+    ///
+    /// **NOTE:** the hook is called on every flag write, even if the value doesn't change
+    pub fn set_flag_hook(&mut self, flag_hook: Option<FlagHook>) {
+        self.flag_hook = flag_hook;
+    }
+
+    /// **NOTE:** should be called once per frame (the keypad is recorded per call, see `start_recording()`)
+    pub fn load_keypad(&mut self, keypad: &Keypad) {
+        self.keypad = keypad.clone();
+
+        if let Some(recording) = &mut self.recording {
+            recording.push(keypad.clone());
+        }
+    }
+
+    /// starts recording the keypad state of every frame (see `load_keypad()` and `tick()`), e.g. for reproducing a bug
+    /// with `play_recording()`
+    ///  - a recording that is already running is discarded
+    pub fn start_recording(&mut self) {
+        self.recording = Some(vec!());
+    }
+
+    /// stops recording and returns the keypad state of every recorded frame (empty if nothing was recorded)
+    pub fn stop_recording(&mut self) -> Vec<Keypad> {
+        return self.recording.take().unwrap_or_default();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        return self.recording.is_some();
+    }
+
+    /// runs one frame per recorded keypad state (see `start_recording()`)
+    ///  - stops early on any outcome other than [`RunOutcome::Continue`] (e.g. a halt or a breakpoint)
+    ///
+    /// **NOTE:** every frame is assumed to take exactly 1/60 of a second, so the replay only matches the recorded run if
+    /// its frames had the same duration (e.g. with `load_and_run()`) and the random generator was seeded the same (see
+    /// `seed_random_generator()`)
+    pub fn play_recording(&mut self, frames: &[Keypad]) -> Result<RunOutcome, Chip8Error> {
+        let mut outcome = RunOutcome::Continue;
+
+        for keypad in frames {
+            outcome = self.tick(keypad.clone(), FRAME_DURATION)?.outcome;
+
+            if outcome != RunOutcome::Continue {
+                break;
+            }
+        }
+
+        return Ok(outcome);
+    }
+
+    /// **NOTE:** should be executed 60 times a second -> every frame
+    fn decrement_timers(&mut self) {
+        // decrement delay timer
+        self.delay_timer -= if self.delay_timer >= 1 { 1 } else { 0 };
+
+        // decrement sound timer
+        if self.sound_timer <= 1 {
+            self.playing_sound = false;
+            self.sound_timer = 0;
+        } else {
+            self.playing_sound = true;
+            self.sound_timer -= 1;
+        }
+    }
+
+    /// copies the bytes into memory without touching anything else (e.g. for poking memory in tests or a debugger)
+    ///
+    /// **NOTE:** use [`Chip8::load_program()`] for loading a ROM, which starts with a clean machine
+    pub fn load_bytes_into_memory(&mut self, data: &[u8], address: u16) {
+        for (offset, byte) in data.iter().enumerate() {
+            self.memory[(address as usize) + offset] = *byte;
+        }
+    }
+
+    pub fn load_opcode_into_memory(&mut self, opcode: u16, address: u16) {
+        let byte_1 = ((opcode & 0xFF00) >> 8) as u8;
+        let byte_2 = (opcode & 0x00FF) as u8;
+
+        self.memory[address as usize] = byte_1;
+        self.memory[(address as usize) + 1] = byte_2;
+    }
+
+    pub fn load_opcodes_into_memory(&mut self, opcodes: &Vec<u16>, mut address: u16) {
+        for opcode in opcodes {
+            self.load_opcode_into_memory(*opcode, address);
+            address = address.wrapping_add(2);
+        }
+    }
+
+    /// parses whitespace- or comma-separated hex words (e.g. `"00E0 A22A 600C"`) and loads them as opcodes starting at `address`
+    ///  - everything after a `;` is treated as a comment until the end of the line
+    ///  - words may optionally be prefixed with `0x`
+    pub fn load_hex_string(&mut self, hex: &str, address: u16) -> Result<(), Chip8Error> {
+        let mut opcodes: Vec<u16> = vec!();
+
+        for line in hex.lines() {
+            // strip comment
+            let line = line.split(';').next().unwrap_or("");
+
+            for token in line.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()) {
+                let digits = token.strip_prefix("0x").unwrap_or(token);
+                let opcode = u16::from_str_radix(digits, 16)
+                    .map_err(|_| Chip8Error::InvalidHexString(format!("the token '{}' is not a valid hex word", token)))?;
+
+                opcodes.push(opcode);
+            }
+        }
+
+        if (address as usize) + opcodes.len() * 2 > self.memory.len() {
+            return Err(Chip8Error::MemoryOverflow(String::from("the opcodes do not fit into memory at the given address")));
+        }
+
+        self.load_opcodes_into_memory(&opcodes, address);
+
+        return Ok(());
+    }
+
+    /// loads the program into a clean machine, so nothing of a previously loaded program is left over
+    ///  - resets the emulator (see [`Chip8::reset()`]), which also installs the program at the program start address
+    ///  - the font, the quirks, the configuration (e.g. breakpoints) and the RPL flags are kept
+    ///
+    /// **NOTE:** the emulator is left untouched if the program doesn't fit into memory
+    pub fn load_program(&mut self, program_data: &[u8]) -> Result<(), Chip8Error> {
+        if program_data.len() > (self.memory.len() - (self.program_start_address as usize)) {
+            return Err(Chip8Error::RomTooLarge(String::from("the program does not fit into its predefined memory space")));
+        }
+
+        self.rom_data = program_data.to_vec();
+        self.rom_checksum = quirks::rom_checksum(program_data);
+        self.reset();
+        info!(target: LOG_TARGET, "loaded a program of {} bytes (checksum 0x{:08X})", program_data.len(), self.rom_checksum);
+
+        return Ok(());
+    }
+
+    /// loads a program that is already in memory (e.g. embedded with `include_bytes!()` or downloaded), so no filesystem
+    /// is needed; the counterpart of `load_rom_from_file()` (see [`Chip8::load_program()`])
+    pub fn load_rom_bytes(&mut self, bytes: &[u8]) -> Result<(), Chip8Error> {
+        return self.load_program(bytes);
+    }
+
+    /// loads the program like [`Chip8::load_program()`] (so the machine is reset first)
+    ///
+    /// **NOTE:** with the `gzip` feature, gzip compressed files (e.g. `.ch8.gz`) are decompressed before loading
+    #[cfg(feature = "std")]
+    pub fn load_rom_from_file(&mut self, path: &Path) -> Result<(), Chip8Error> {
+        let mut file = File::open(path).map_err(|err| Chip8Error::IO(err))?;
+
+        let mut file_data: Vec<u8> = vec!();
+        file.read_to_end(&mut file_data).map_err(|err| Chip8Error::IO(err))?;
+
+        #[cfg(feature = "gzip")]
+        let file_data = if file_data.starts_with(&GZIP_MAGIC) { self.decompress_rom(&file_data)? } else { file_data };
+
+        self.load_program(&file_data)?;
+
+        return Ok(());
+    }
+
+    /// decompresses at most as many bytes as fit into memory, so a huge (or malicious) archive is rejected early
+    #[cfg(feature = "gzip")]
+    fn decompress_rom(&self, compressed_data: &[u8]) -> Result<Vec<u8>, Chip8Error> {
+        let max_size = self.memory.len() - (self.program_start_address as usize);
+
+        let mut program_data: Vec<u8> = vec!();
+        GzDecoder::new(compressed_data)
+            .take((max_size as u64) + 1)
+            .read_to_end(&mut program_data)
+            .map_err(|err| Chip8Error::IO(err))?;
+
+        if program_data.len() > max_size {
+            return Err(Chip8Error::RomTooLarge(String::from("the decompressed program does not fit into its predefined memory space")));
+        }
+
+        return Ok(program_data);
+    }
+
+    /// reads the program from any source (e.g. stdin or embedded bytes) and returns its size in bytes
+    #[cfg(feature = "std")]
+    pub fn load_rom_from_reader<R: Read>(&mut self, mut reader: R) -> Result<usize, Chip8Error> {
+        let mut program_data: Vec<u8> = vec!();
+        reader.read_to_end(&mut program_data).map_err(|err| Chip8Error::IO(err))?;
+
+        self.load_program(&program_data)?;
+
+        return Ok(program_data.len());
+    }
+
+    pub fn load_font(&mut self, font_data: &[u8]) -> Result<(), Chip8Error> {
+        if font_data.len() != FONT_DATA_SIZE {
+            return Err(Chip8Error::InvalidFontData(String::from("the fonts data does not fit into its predefined memory space")));
+        }
+
+        self.load_bytes_into_memory(font_data, FONT_START_ADDRESS);
+        self.font_data = font_data.to_vec();
+
+        return Ok(());
+    }
+
+    /// installs the big font of `FX30` (10 glyphs for the digits 0-9 with 10 bytes each)
+    pub fn load_big_font(&mut self, big_font_data: &[u8]) -> Result<(), Chip8Error> {
+        if big_font_data.len() != BIG_FONT_DATA_SIZE {
+            return Err(Chip8Error::InvalidFontData(String::from("the big font data does not fit into its predefined memory space")));
+        }
+
+        self.load_bytes_into_memory(big_font_data, BIG_FONT_START_ADDRESS);
+        self.big_font_data = big_font_data.to_vec();
+
+        return Ok(());
+    }
+
+    /// writes the whole memory (font, program and data) to a file as raw bytes, e.g. for comparing the state of
+    /// self-modifying code at different points of the execution
+    #[cfg(feature = "std")]
+    pub fn dump_memory(&self, path: &Path) -> Result<(), Chip8Error> {
+        return fs::write(path, &self.memory).map_err(|err| Chip8Error::IO(err));
+    }
+
+    /// writes the RPL flags to a file (e.g. so that high scores survive a restart, like on the original hardware)
+    #[cfg(feature = "std")]
+    pub fn save_rpl_flags(&self, path: &Path) -> Result<(), Chip8Error> {
+        return fs::write(path, self.rpl_flags).map_err(|err| Chip8Error::IO(err));
+    }
+
+    /// reads the RPL flags from a file written by `save_rpl_flags()`
+    ///
+    /// **NOTE:** a missing file is treated as all flags being 0
+    #[cfg(feature = "std")]
+    pub fn load_rpl_flags(&mut self, path: &Path) -> Result<(), Chip8Error> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec!(),
+            Err(err) => return Err(Chip8Error::IO(err)),
+        };
+
+        if data.len() > RPL_FLAG_COUNT {
+            return Err(Chip8Error::IO(io::Error::new(io::ErrorKind::InvalidData, format!("the RPL flags file contains {} instead of {} bytes", data.len(), RPL_FLAG_COUNT))));
+        }
+
+        self.rpl_flags = [0; RPL_FLAG_COUNT];
+        self.rpl_flags[..data.len()].copy_from_slice(&data);
+
+        return Ok(());
+    }
+
+    pub fn get_rpl_flags(&self) -> &[u8; RPL_FLAG_COUNT] {
+        return &self.rpl_flags;
+    }
+
+    /// **NOTE:** panics if `reg_id` does not refer to one of the 16 registers (`0x0` - `0xF`)
+    pub fn set_register(&mut self, reg_id: u8, value: u8) {
+        check_reg_id(reg_id);
+        self.registers[reg_id as usize] = value;
+    }
+
+    /// **NOTE:** panics if `reg_id` does not refer to one of the 16 registers (`0x0` - `0xF`)
+    pub fn get_register(&self, reg_id: u8) -> u8 {
+        check_reg_id(reg_id);
+        return self.registers[reg_id as usize];
+    }
+
+    /// like `set_register()`, but returns an error instead of panicking if `reg_id` does not refer to one of the 16
+    /// registers
+    ///
+    /// **NOTE:** `set_register()` is the setter to use: an invalid ID is a bug of the caller, so it panics like
+    /// `get_register()`
+    #[deprecated(note = "use `set_register()` instead (check IDs from user input against `0xF` first)")]
+    pub fn load_register(&mut self, reg_id: u8, value: u8) -> Result<(), Chip8Error> {
+        if reg_id > 0xF {
+            return Err(Chip8Error::InvalidRegister(format!("there is no register with the ID 0x{:02x}!", reg_id)));
+        }
+
+        self.set_register(reg_id, value);
+
+        return Ok(());
+    }
+
+    pub fn load_index_reg(&mut self, address: u16) {
+        self.set_index_reg(address);
+    }
+
+    pub fn load_registers(&mut self, values: &[u8; 16]) {
+        for (reg_id, value) in values.iter().enumerate() {
+            self.registers[reg_id] = *value;
+        }
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+        self.screen.set_sprite_wrap(quirks.sprite_wrap);
+        self.screen.set_lores_scroll_half(quirks.lores_scroll_half);
+    }
+
+    pub fn get_quirks(&self) -> Quirks {
+        return self.quirks;
+    }
+
+    /// restarts the random numbers of `CXNN` from `seed`
+    ///
+    /// **NOTE:** the emulator always starts with the same seed, so the frontend has to pass in a random one (e.g. from
+    /// the operating system or a hardware random generator) unless the run should be reproducible
+    pub fn seed_random_generator(&mut self, seed: u64) {
+        self.random_generator = SmallRng::seed_from_u64(seed);
+    }
+
+    /// returns the currently loaded program
+    pub fn get_rom_data(&self) -> &[u8] {
+        return &self.rom_data;
+    }
+
+    /// a stable identity of the loaded program (e.g. for per-program settings)
+    pub fn loaded_rom_checksum(&self) -> u32 {
+        return self.rom_checksum;
+    }
+
+    pub fn set_collision_mode(&mut self, collision_mode: CollisionMode) {
+        self.screen.set_collision_mode(collision_mode);
+    }
+
+    /// **NOTE:** anything other than [`SpriteDrawMode::Xor`] is only meant for debugging, as it breaks the programs
+    pub fn set_sprite_draw_mode(&mut self, draw_mode: SpriteDrawMode) {
+        self.screen.set_draw_mode(draw_mode);
+    }
+
+    pub fn get_sprite_draw_mode(&self) -> SpriteDrawMode {
+        return self.screen.get_draw_mode();
+    }
+
+    pub fn get_memory(&self) -> &[u8] {
+        return &self.memory;
+    }
+
+    pub fn get_program_start_address(&self) -> u16 {
+        return self.program_start_address;
+    }
+
+    pub fn get_program_counter(&self) -> u16 {
+        return self.program_counter;
+    }
+
+    pub fn get_index_reg(&self) -> u16 {
+        return self.index_reg;
+    }
+
+    /// sets how many return addresses the stack holds (e.g. 12 for the COSMAC VIP or 16 for SUPER-CHIP)
+    ///
+    /// **NOTE:** this replaces the stack, so it should be called before running the program
+    pub fn set_stack_depth(&mut self, depth: usize) {
+        self.stack = Stack::new(depth);
+    }
+
+    /// sets the value the memory is filled with outside of the fonts and the program (e.g. `0xFF` for reproducing a
+    /// program that reads uninitialized memory)
+    ///
+    /// **NOTE:** this resets the emulator, so it should be called before running the program
+    pub fn set_memory_pattern(&mut self, memory_pattern: MemoryPattern) {
+        self.memory_pattern = memory_pattern;
+        self.reset();
+    }
+
+    pub fn get_memory_pattern(&self) -> MemoryPattern {
+        return self.memory_pattern;
+    }
+
+    /// returns the number of return addresses that are currently on the stack
+    pub fn get_stack_depth(&self) -> usize {
+        return self.stack.depth();
+    }
+
+    /// returns the return addresses of the active subroutine calls, from the first to the last call (e.g. for showing
+    /// the call chain in a debugger)
+    pub fn call_stack(&self) -> &[u16] {
+        return self.stack.as_slice();
+    }
+
+    pub fn get_stack_pointer(&self) -> u16 {
+        return self.stack.stack_pointer;
+    }
+
+    pub fn get_delay_timer(&self) -> u8 {
+        return self.delay_timer;
+    }
+
+    pub fn get_sound_timer(&self) -> u8 {
+        return self.sound_timer;
+    }
+
+    pub fn playing_sound(&self) -> bool {
+        return self.playing_sound;
+    }
+
+    /// returns the XO-CHIP audio pattern, which is played (looped) instead of the beep while the sound timer runs
+    ///
+    /// **NOTE:** returns `None` until the program loads a pattern, in which case the classic beep is played
+    pub fn get_audio_pattern(&self) -> Option<[u8; AUDIO_PATTERN_SIZE]> {
+        return self.audio_pattern;
+    }
+
+    pub fn get_pitch(&self) -> u8 {
+        return self.pitch;
+    }
+
+    /// returns the playback rate of the audio pattern in bits per second (`4000 * 2 ^ ((pitch - 64) / 48)`)
+    ///
+    /// **NOTE:** needs `std` for the floating point math (`get_pitch()` is available without it)
+    #[cfg(feature = "std")]
+    pub fn get_audio_pattern_rate(&self) -> f32 {
+        return 4000.0 * 2f32.powf((self.pitch as f32 - DEFAULT_PITCH as f32) / 48.0);
+    }
+
+    pub fn reached_end_of_file(&self) -> bool {
+        return self.reached_end_of_file;
+    }
+
+    /// number of instructions executed since the creation or the last reset (independent of any timing)
+    pub fn instructions_executed(&self) -> u64 {
+        return self.instructions_executed;
+    }
+
+    /// number of frames run since the creation or the last reset
+    pub fn frames_run(&self) -> u64 {
+        return self.frames_run;
+    }
+
+    /// total time passed to `run_frame()` and `advance()` since the creation or the last reset
+    pub fn time_elapsed(&self) -> Duration {
+        return self.time_elapsed;
+    }
+
+    /// returns the achieved number of instructions per second (`instructions_executed()` / `time_elapsed()`)
+    ///  - lower than the configured rate if the host can't keep up (see `max_instructions_per_frame`)
+    ///
+    /// **NOTE:** returns `0.0` as long as no time has passed
+    pub fn effective_ips(&self) -> f64 {
+        let seconds = self.time_elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        return self.instructions_executed as f64 / seconds;
+    }
+
+    /// returns `true` if the emulator stopped executing instructions (see [`Chip8::reset()`] or [`Chip8::soft_reset()`] to
+    /// run the program again)
+    pub fn is_halted(&self) -> bool {
+        return self.halted;
+    }
+
+    pub fn reset_state(&mut self) {
+        self.reached_end_of_file = false;
+        self.halted = false;
+        self.program_counter = self.program_start_address;
+        for val in self.registers.iter_mut() {
+            *val = 0;
+        }
+    }
+
+    /// puts the emulator back into the state right after loading the font and program
+    ///  - clears memory (see [`Chip8::set_memory_pattern()`]), screen, stack, timers and all registers (including I)
+    ///  - reinstalls the loaded fonts (or the default ones) and program
+    pub fn reset(&mut self) {
+        let fill_byte = self.memory_pattern.byte();
+        for byte in self.memory.iter_mut() {
+            *byte = fill_byte;
+        }
+
+        let font_data = self.font_data.clone();
+        self.load_bytes_into_memory(&font_data, FONT_START_ADDRESS);
+
+        let big_font_data = self.big_font_data.clone();
+        self.load_bytes_into_memory(&big_font_data, BIG_FONT_START_ADDRESS);
+
+        let rom_data = self.rom_data.clone();
+        self.load_bytes_into_memory(&rom_data, self.program_start_address);
+
+        self.reset_volatile_state();
+    }
+
+    /// restarts the loaded program without reinstalling it (e.g. for a quick restart)
+    ///  - keeps the memory below the program start (the fonts) and the program region (the length of the loaded ROM)
+    ///    as they are, the rest of the memory is cleared (see [`Chip8::set_memory_pattern()`])
+    ///  - resets the screen, stack, timers and all registers (including I) like `reset()`
+    ///
+    /// **NOTE:** changes the program made to its own code are kept (use `reset()` to restore the original program)
+    pub fn soft_reset(&mut self) {
+        let program_end = (self.program_start_address as usize + self.rom_data.len()).min(self.memory.len());
+
+        let fill_byte = self.memory_pattern.byte();
+        for byte in self.memory[program_end..].iter_mut() {
+            *byte = fill_byte;
+        }
+
+        self.reset_volatile_state();
+    }
+
+    /// resets everything but the memory (see `reset()` and `soft_reset()`)
+    fn reset_volatile_state(&mut self) {
+        self.registers = [0; 16];
+        self.index_reg = 0x0;
+        self.program_counter = self.program_start_address;
+        self.stack = Stack::new(self.stack.capacity());
+        self.screen.reset();
+        self.keypad = Keypad::new();
+        self.delay_timer = 0;
+        self.sound_timer = 0;
+        self.playing_sound = false;
+        self.audio_pattern = None;
+        self.pitch = DEFAULT_PITCH;
+        self.exec_time = Duration::new(0, 0);
+        self.timer_time = Duration::new(0, 0);
+        self.reached_end_of_file = false;
+        self.halted = false;
+        self.instructions_executed = 0;
+        self.frames_run = 0;
+        self.time_elapsed = Duration::new(0, 0);
+        self.recent_instructions.clear();
+        self.resumed_breakpoint = None;
+        self.watchpoint_hit = None;
+        self.last_code_write = None;
+    }
+
+    /// serializes the complete emulator state (including a versioned header)
+    #[cfg(feature = "std")]
+    pub fn save_state(&self) -> Vec<u8> {
+        let save_state = SaveState {
+            registers: self.registers,
+            program_counter: self.program_counter,
+            memory: self.memory.clone(),
+            index_reg: self.index_reg,
+            stack_memory: self.stack.memory.to_vec(),
+            stack_pointer: self.stack.stack_pointer,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            audio_pattern: self.audio_pattern,
+            pitch: self.pitch,
+            frame_buffer: self.screen.get_frame_buffer().clone(),
+            second_plane: self.screen.get_second_plane().clone(),
+            selected_planes: self.screen.get_selected_planes(),
+            reached_end_of_file: self.reached_end_of_file,
+            halted: self.halted,
+        };
+
+        return save_state.to_bytes();
+    }
+
+    /// restores a state created by `save_state()`; the emulator is left untouched if the data is rejected
+    #[cfg(feature = "std")]
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), SaveStateError> {
+        let save_state = SaveState::from_bytes(data)?;
+
+        if save_state.memory.len() != self.memory.len() || save_state.stack_memory.len() != self.stack.memory.len() {
+            return Err(SaveStateError::Corrupted(String::from("the memory layout does not match the emulator")));
+        }
+
+        if save_state.stack_pointer as usize > self.stack.capacity() {
+            return Err(SaveStateError::Corrupted(String::from("the stack pointer exceeds the stack")));
+        }
+
+        let frame_buffer = &save_state.frame_buffer;
+        let second_plane = &save_state.second_plane;
+        if !frame_buffer.is_consistent() || !second_plane.is_consistent()
+            || frame_buffer.width() != second_plane.width() || frame_buffer.height() != second_plane.height() {
+            return Err(SaveStateError::Corrupted(String::from("the frame buffers do not match their resolution")));
+        }
+
+        self.registers = save_state.registers;
+        self.program_counter = save_state.program_counter;
+        self.memory = save_state.memory;
+        self.index_reg = save_state.index_reg;
+        self.stack.memory.copy_from_slice(&save_state.stack_memory);
+        self.stack.stack_pointer = save_state.stack_pointer;
+        self.delay_timer = save_state.delay_timer;
+        self.sound_timer = save_state.sound_timer;
+        self.audio_pattern = save_state.audio_pattern;
+        self.pitch = save_state.pitch;
+        self.screen.set_frame_buffer(save_state.frame_buffer);
+        self.screen.set_second_plane(save_state.second_plane);
+        self.screen.select_planes(save_state.selected_planes);
+        self.reached_end_of_file = save_state.reached_end_of_file;
+        self.halted = save_state.halted;
+
+        return Ok(());
+    }
+
+    /// copies only the display (e.g. to undo a draw or to compare frames)
+    pub fn screen_snapshot(&self) -> ScreenSnapshot {
+        return self.screen.snapshot();
+    }
+
+    pub fn restore_screen(&mut self, snapshot: &ScreenSnapshot) {
+        self.screen.restore(snapshot);
+    }
+
+    pub fn frame(&self) -> &FrameBuffer {
+        return self.screen.get_frame_buffer();
+    }
+
+    /// returns the second plane (XO-CHIP), which is combined with `frame()` into the palette colors when rendering
+    pub fn second_plane(&self) -> &FrameBuffer {
+        return self.screen.get_second_plane();
+    }
+
+    /// returns an owned copy of the frame buffer (e.g. for handing it to a render thread), see `frame_version()`
+    pub fn snapshot_frame(&self) -> FrameBuffer {
+        return self.screen.get_frame_buffer().clone();
+    }
+
+    /// returns a number that increases whenever the frame buffer changes, so a frontend can skip redrawing (or taking
+    /// another snapshot) if it's the same as last time
+    pub fn frame_version(&self) -> u64 {
+        return self.screen.get_version();
+    }
+
+    #[deprecated(note = "use `frame()` instead, which also supports other resolutions")]
+    pub fn get_frame_buffer(&self) -> [[bool; (screen::WIDTH as usize)]; (screen::HEIGHT as usize)] {
+        return self.screen.get_frame_buffer().to_lores_array();
+    }
+
+    /// returns a complete human-readable snapshot of the machine (registers, stack, timers, quirks and the screen as
+    /// ASCII art), e.g. for attaching it to a bug report
+    pub fn dump_state_text(&self) -> String {
+        let mut text = String::new();
+
+        // writing to a string can't fail
+        writeln!(text, "REGISTERS:").unwrap();
+        for (i, reg) in self.registers.iter().enumerate() {
+            writeln!(text, "\tV{:X}: 0x{:02x} = {:3}", i, reg, reg).unwrap();
+        }
+        writeln!(text, "PC: 0x{:04x}", self.program_counter).unwrap();
+        writeln!(text, "I: 0x{:04x}", self.index_reg).unwrap();
+        writeln!(text, "SP: {} (depth {}/{})", self.stack.stack_pointer, self.stack.depth(), self.stack.capacity()).unwrap();
+
+        // return addresses from the bottom to the top of the stack
+        writeln!(text, "STACK:").unwrap();
+        for return_address in self.stack.as_slice() {
+            writeln!(text, "\t0x{:04x}", return_address).unwrap();
+        }
+
+        writeln!(text, "DELAY TIMER: {}", self.delay_timer).unwrap();
+        writeln!(text, "SOUND TIMER: {} (playing sound: {})", self.sound_timer, self.playing_sound).unwrap();
+        writeln!(text, "QUIRKS: {:?}", self.quirks).unwrap();
+
+        let frame = self.frame();
+        writeln!(text, "SCREEN ({}x{}):", frame.width(), frame.height()).unwrap();
+        text.push_str(&frame.to_ascii());
+
+        return text;
+    }
+
+    /// logs the registers, timers and the next instruction at the debug level (see [`Chip8::dump_state_text()`] for a
+    /// complete snapshot)
+    pub fn log_debug_info(&self) {
+        debug!(target: LOG_TARGET, "==== CHIP-8 CPU DEBUG INFO (START) ====");
+
+        // output registers
+        debug!(target: LOG_TARGET, "REGISTERS:");
+        for (i, reg) in self.registers.iter().enumerate() {
+            debug!(target: LOG_TARGET, "\t{:02}: 0x{:04x} = {:3}", i, reg, reg);
+        }
+
+        // output special registers and timers
+        debug!(target: LOG_TARGET, "PC: 0x{:04x}", self.program_counter);
+        debug!(target: LOG_TARGET, "I: 0x{:04x}", self.index_reg);
+        debug!(target: LOG_TARGET, "SP: {} (depth {}/{})", self.stack.stack_pointer, self.stack.depth(), self.stack.capacity());
+        debug!(target: LOG_TARGET, "DELAY TIMER: {}", self.delay_timer);
+        debug!(target: LOG_TARGET, "SOUND TIMER: {} (playing sound: {})", self.sound_timer, self.playing_sound);
+
+        // output next instruction
+        debug!(target: LOG_TARGET, "NEXT INSTRUCTION: {}", disassembler::disassemble(self.fetch_instruction()));
+
+        debug!(target: LOG_TARGET, "==== CHIP-8 CPU DEBUG INFO (END) ====");
+    }
+}
+
+/// creates an emulator with the default quirks and font and the program loaded at [`DEFAULT_PROGRAM_START_ADDRESS`],
+/// e.g. `Chip8::try_from(include_bytes!("pong.ch8").as_slice())`
+///
+/// **NOTE:** fails with [`Chip8Error::RomTooLarge`] if the program doesn't fit into memory
+impl TryFrom<&[u8]> for Chip8 {
+    type Error = Chip8Error;
+
+    fn try_from(program_data: &[u8]) -> Result<Self, Self::Error> {
+        let mut chip8 = Chip8::new(Quirks::default());
+        chip8.load_program(program_data)?;
+
+        return Ok(chip8);
+    }
+}
+
+/// same as the conversion from a slice, but takes the array of `include_bytes!()` directly
+impl<const N: usize> TryFrom<&[u8; N]> for Chip8 {
+    type Error = Chip8Error;
+
+    fn try_from(program_data: &[u8; N]) -> Result<Self, Self::Error> {
+        return Chip8::try_from(program_data.as_slice());
+    }
+}
+
+fn check_reg_id(reg_id: u8) {
+    if reg_id > 0xF {
+        panic!("invalid register ID 0x{:02x}!", reg_id);
+    }
+}
+
+// the tests use files and save-states, which need `std` (see the smoke test in `lib.rs` for the core without it)
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::*;
+    use crate::screen::SpriteWrap;
+
+    fn init_emulator() -> Chip8 {
+        let chip8 = Chip8::new(Quirks::default());
+
+        return chip8;
+    }
+
+    fn run_emulator(chip8: &mut Chip8) {
+        while chip8.exec_next_instruction().expect("an error occurred during emulator execution") {}
+    }
+
+    #[test]
+    fn halt_stops_execution() {
+        let mut chip8 = init_emulator();
+
+        // load opcodes
+        let opcodes: Vec<u16> = vec!(0x0000, 0x6011);
+        chip8.load_opcodes_into_memory(&opcodes, chip8.program_start_address);
+
+        chip8.exec_next_instruction().expect("an error occurred during emulator execution");
+        chip8.exec_next_instruction().expect("an error occurred during emulator execution");
+
+        // verify result
+        assert!(chip8.is_halted(), "failed to halt on the empty instruction");
+        assert_eq!(chip8.registers[0], 0, "failed to stop executing instructions after the halt");
+        assert_eq!(chip8.program_counter, chip8.program_start_address + 2, "failed to keep the program counter at the halt");
+
+        chip8.reset_state();
+        assert!(!chip8.is_halted(), "failed to un-halt on reset");
+    }
+
+    fn opcodes_to_bytes(opcodes: &[u16]) -> Vec<u8> {
+        return opcodes.iter().flat_map(|opcode| opcode.to_be_bytes()).collect();
+    }
+
+    fn assert_same_state(chip8: &Chip8, expected: &Chip8) {
+        assert_eq!(chip8.memory, expected.memory, "memory differs");
+        assert_eq!(chip8.registers, expected.registers, "registers differ");
+        assert_eq!(chip8.index_reg, expected.index_reg, "index register differs");
+        assert_eq!(chip8.program_counter, expected.program_counter, "program counter differs");
+        assert_eq!(chip8.stack.stack_pointer, expected.stack.stack_pointer, "stack pointer differs");
+        assert_eq!(chip8.delay_timer, expected.delay_timer, "delay timer differs");
+        assert_eq!(chip8.sound_timer, expected.sound_timer, "sound timer differs");
+        assert_eq!(chip8.frame(), expected.frame(), "screen differs");
+        assert_eq!(chip8.halted, expected.halted, "halt state differs");
+    }
+
+    #[test]
+    fn reset() {
+        let mut chip8 = init_emulator();
+
+        // set registers, dump them into memory, call a subroutine, set timers and draw a sprite
+        let program_data = opcodes_to_bytes(&[0x600F, 0x610A, 0xA300, 0xF155, 0x2210, 0x0000, 0x0000, 0x0000, 0xF015, 0xF118, 0xD015, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+
+        let mut expected = init_emulator();
+        expected.load_program(&program_data).expect("failed to load the program");
+
+        run_emulator(&mut chip8);
+        chip8.reset();
+
+        // verify result
+        assert_same_state(&chip8, &expected);
+    }
+
+    #[test]
+    fn decode_all_opcodes_without_panic() {
+        for opcode in 0x0000..=0xFFFF {
+            let mut chip8 = init_emulator();
+
+            chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+
+            // unknown opcodes have to return an error instead of panicking
+            let _ = chip8.exec_next_instruction();
+        }
+    }
+
+    #[test]
+    fn unknown_opcode_policy() {
+        // V0 = 1; unknown instruction; V0 = 2
+        let program_data = opcodes_to_bytes(&[0x6001, 0x5001, 0x6002, 0x0000]);
+
+        // (policy, expected outcome, expected V0)
+        let cases = [
+            (UnknownOpcodePolicy::Ignore, Some(RunOutcome::Halted), 2),
+            (UnknownOpcodePolicy::Halt, Some(RunOutcome::Halted), 1),
+            (UnknownOpcodePolicy::Error, None, 1),
+        ];
+
+        for (unknown_opcode_policy, expected_outcome, expected_val) in cases {
+            let mut chip8 = Chip8::new(Quirks { unknown_opcode_policy, ..Quirks::default() });
+            chip8.load_program(&program_data).expect("failed to load the program");
+
+            let outcome = chip8.run_instructions(10).ok();
+            assert_eq!(outcome, expected_outcome, "failed to apply the policy {:?}", unknown_opcode_policy);
+            assert_eq!(chip8.registers[0], expected_val, "failed to continue or stop according to the policy {:?}", unknown_opcode_policy);
+            assert_eq!(chip8.get_program_counter(), if expected_val == 2 { 0x208 } else { 0x204 }, "failed to move past the instruction with the policy {:?}", unknown_opcode_policy);
+        }
+    }
+
+    #[test]
+    fn random_memory_never_panics() {
+        use rand::SeedableRng;
+
+        // seeded, so a failing image can be reproduced
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xC8);
+        let sprite_wraps = [SpriteWrap::OriginOnly, SpriteWrap::Always, SpriteWrap::Never];
+
+        for idx in 0..1000 {
+            // ignore unknown instructions, so that most images run for a while instead of stopping at the first error
+            let quirks = Quirks {
+                assign_before_shift: rng.gen(),
+                set_flag_on_index_overflow: rng.gen(),
+                sprite_wrap: sprite_wraps[idx % sprite_wraps.len()],
+                unknown_opcode_policy: UnknownOpcodePolicy::Ignore,
+                ..Quirks::default()
+            };
+            let mut chip8 = Chip8::new(quirks);
+
+            let mut memory_image = vec!(0; chip8.memory.len());
+            rng.fill(&mut memory_image[..]);
+            chip8.load_bytes_into_memory(&memory_image, 0);
+            chip8.keypad.set_key(rng.gen_range(0, 16));
+
+            // errors (e.g. a stack overflow) are fine, only panics fail the test
+            let _ = chip8.run_instructions(1000);
+            let _ = chip8.run_frame(FRAME_DURATION);
+        }
+    }
+
+    #[test]
+    fn unknown_opcode_error() {
+        let mut chip8 = init_emulator();
+        chip8.load_program(&opcodes_to_bytes(&[0x6001, 0x5001])).expect("failed to load the program");
+
+        let result = chip8.run_instructions(2);
+
+        // verify result
+        match result {
+            Err(Chip8Error::UnknownOpcode { opcode, address }) => {
+                assert_eq!(opcode, 0x5001, "failed to report the opcode");
+                assert_eq!(address, 0x202, "failed to report the address");
+            }
+            _ => panic!("failed to report the unknown opcode; result: {:?}", result),
+        }
+        assert_eq!(
+            Chip8Error::UnknownOpcode { opcode: 0x5001, address: 0x202 }.to_string(),
+            "there is no implementation for the instruction 0x5001 that was found at mem address 0x0202!",
+            "failed to format the error",
+        );
+    }
+
+    #[test]
+    fn load_rom_from_reader() {
+        let mut chip8 = init_emulator();
+
+        let program_data = opcodes_to_bytes(&[0x6005, 0x6107, 0x8014]);
+        let size = chip8.load_rom_from_reader(io::Cursor::new(program_data.clone())).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(size, program_data.len(), "failed to report the program size");
+        assert_eq!(chip8.registers[0], 12, "failed to correctly execute the loaded program; V0: {}", chip8.registers[0]);
+    }
+
+    #[test]
+    fn load_rom_bytes() {
+        let mut chip8 = Chip8::with_memory_layout(DEFAULT_MEMORY_SIZE, ETI_660_PROGRAM_START_ADDRESS, Quirks::default());
+
+        let program_data: &[u8] = &[0x60, 0x05, 0x00, 0x00];
+        chip8.load_rom_bytes(program_data).expect("failed to load the program");
+
+        // verify result
+        let start = ETI_660_PROGRAM_START_ADDRESS as usize;
+        assert_eq!(&chip8.get_memory()[start..start + 4], program_data, "failed to place the program at the start address");
+        assert_eq!(chip8.get_rom_data(), program_data, "failed to keep the program data");
+
+        let oversized_data = vec![0xFF; DEFAULT_MEMORY_SIZE - start + 1];
+        let result = chip8.load_rom_bytes(&oversized_data);
+        assert!(matches!(result, Err(Chip8Error::RomTooLarge(_))), "failed to reject the oversized program");
+    }
+
+    #[test]
+    fn load_rom_from_reader_too_large() {
+        let mut chip8 = init_emulator();
+
+        let program_data = vec![0xFF; DEFAULT_MEMORY_SIZE];
+        let result = chip8.load_rom_from_reader(io::Cursor::new(program_data));
+
+        // verify result
+        assert!(matches!(result, Err(Chip8Error::RomTooLarge(_))), "failed to reject the oversized program");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn load_gzipped_rom_from_file() {
+        use std::io::Write;
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let rom = include_bytes!("../programs/welcome.ch8");
+
+        let mut encoder = GzEncoder::new(vec!(), Compression::default());
+        encoder.write_all(rom).expect("failed to compress the program");
+        let path = std::env::temp_dir().join("chip8_welcome.ch8.gz");
+        std::fs::write(&path, encoder.finish().expect("failed to compress the program")).expect("failed to write the compressed program");
+
+        let mut chip8 = init_emulator();
+        chip8.load_rom_from_file(&path).expect("failed to load the compressed program");
+
+        // verify result
+        assert_eq!(chip8.get_rom_data(), rom, "failed to decompress the program");
+
+        // a program that only fits into memory when it's compressed
+        let mut encoder = GzEncoder::new(vec!(), Compression::default());
+        encoder.write_all(&vec![0x00; DEFAULT_MEMORY_SIZE]).expect("failed to compress the program");
+        std::fs::write(&path, encoder.finish().expect("failed to compress the program")).expect("failed to write the compressed program");
+
+        let result = chip8.load_rom_from_file(&path);
+        std::fs::remove_file(&path).expect("failed to remove the compressed program");
+
+        assert!(matches!(result, Err(Chip8Error::RomTooLarge(_))), "failed to reject the oversized decompressed program");
+    }
+
+    #[test]
+    fn try_from_program_data() {
+        let rom = include_bytes!("../programs/welcome.ch8");
+
+        let chip8 = Chip8::try_from(rom.as_slice()).expect("failed to create the emulator from the program");
+
+        // verify result
+        let start_address = DEFAULT_PROGRAM_START_ADDRESS as usize;
+        assert_eq!(&chip8.memory[start_address..(start_address + 4)], &rom[..4], "failed to load the program at 0x200");
+        assert_eq!(&chip8.memory[(FONT_START_ADDRESS as usize)..(FONT_START_ADDRESS as usize + FONT_DATA_SIZE)], &DEFAULT_FONT_DATA, "failed to install the default font");
+
+        let result = Chip8::try_from(&[0xFF; DEFAULT_MEMORY_SIZE]);
+        assert!(matches!(result, Err(Chip8Error::RomTooLarge(_))), "failed to reject the oversized program");
+    }
+
+    #[test]
+    fn save_and_load_state() {
+        let mut chip8 = init_emulator();
+
+        let program_data = opcodes_to_bytes(&[0x600F, 0x610A, 0xA300, 0xF155, 0x2210, 0x0000, 0x0000, 0x0000, 0xF015, 0xF118, 0xD015, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        let mut restored = init_emulator();
+        restored.load_state(&chip8.save_state()).expect("failed to load the save-state");
+
+        // verify result
+        assert_same_state(&restored, &chip8);
+    }
+
+    /// returns the save-state of a fresh emulator and an emulator that ran a short program (to load it into)
+    fn init_save_state() -> (SaveState, Chip8) {
+        let mut chip8 = init_emulator();
+        chip8.load_program(&opcodes_to_bytes(&[0x600F, 0x0000])).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        let save_state = SaveState::from_bytes(&init_emulator().save_state()).expect("failed to read the save-state");
+        return (save_state, chip8);
+    }
+
+    #[test]
+    fn reject_invalid_stack_pointer() {
+        let (mut save_state, mut chip8) = init_save_state();
+        save_state.stack_pointer = (chip8.stack.capacity() + 1) as u16;
+
+        let result = chip8.load_state(&save_state.to_bytes());
+
+        // verify result
+        assert!(matches!(result, Err(SaveStateError::Corrupted(_))), "failed to reject the stack pointer beyond the stack");
+        assert_eq!(chip8.registers[0x0], 0x0F, "falsely modified the emulator");
+    }
+
+    #[test]
+    fn reject_invalid_frame_buffers() {
+        // fewer pixels than the resolution needs (can't be created through the API, only by a corrupted save-state)
+        let (mut save_state, mut chip8) = init_save_state();
+        let pixels: Vec<bool> = vec![false; 10];
+        let serialized = bincode::serialize(&(screen::WIDTH, screen::HEIGHT, pixels)).expect("failed to serialize the frame buffer");
+        save_state.frame_buffer = bincode::deserialize(&serialized).expect("failed to deserialize the frame buffer");
+
+        let result = chip8.load_state(&save_state.to_bytes());
+        assert!(matches!(result, Err(SaveStateError::Corrupted(_))), "failed to reject the frame buffer with missing pixels");
+
+        // planes of different resolutions
+        let (mut save_state, mut chip8) = init_save_state();
+        save_state.second_plane = FrameBuffer::new(128, 64);
+
+        let result = chip8.load_state(&save_state.to_bytes());
+        assert!(matches!(result, Err(SaveStateError::Corrupted(_))), "failed to reject the planes of different resolutions");
+        assert_eq!(chip8.registers[0x0], 0x0F, "falsely modified the emulator");
+    }
+
+    #[test]
+    fn tick() {
+        let mut chip8 = init_emulator();
+
+        // wait for key 0x5, then start the sound and draw the font sprite of the key
+        let program_data = opcodes_to_bytes(&[0xF00A, 0x610A, 0xF118, 0xF029, 0xD005, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+
+        let frame_duration = Duration::from_nanos(1_000_000_000 / 60);
+
+        let result = chip8.tick(Keypad::new(), frame_duration).expect("failed to tick");
+        assert_eq!(result.outcome, RunOutcome::Continue, "failed to correctly wait for a keypress");
+        assert!(!result.beep, "failed to correctly report the sound state");
+
+        let mut keypad = Keypad::new();
+        keypad.set_key(0x5);
+        chip8.tick(keypad, frame_duration).expect("failed to tick");
+
+        let result = chip8.tick(Keypad::new(), frame_duration).expect("failed to tick");
+
+        // verify result
+        assert_eq!(result.outcome, RunOutcome::Halted, "failed to correctly report the halt");
+        assert!(result.beep, "failed to correctly report the sound state");
+        assert!(result.frame.pixels().iter().any(|&pixel| pixel), "failed to correctly return the frame");
+    }
+
+    #[test]
+    fn effective_ips() {
+        let mut chip8 = init_emulator();
+        chip8.set_cycles_per_frame(10);
+
+        // load opcodes (infinite loop)
+        chip8.load_opcode_into_memory(0x1200, chip8.program_start_address);
+        assert_eq!(chip8.effective_ips(), 0.0, "falsely reported a rate before any time passed");
+
+        // 60 frames with 10 instructions each
+        for _ in 0..60 {
+            chip8.advance(FRAME_DURATION).expect("an error occurred during emulator execution");
+        }
+
+        // verify result
+        assert_eq!(chip8.instructions_executed(), 600, "failed to run the instructions of every frame");
+        assert!((chip8.effective_ips() - 600.0).abs() < 0.01, "failed to correctly compute the rate; rate: {}", chip8.effective_ips());
+    }
+
+    #[test]
+    fn advance_by_fixed_durations() {
+        let mut chip8 = init_emulator();
+        chip8.set_cycles_per_frame(5);
+        chip8.delay_timer = 10;
+
+        // load opcodes (infinite loop)
+        chip8.load_opcode_into_memory(0x1200, chip8.program_start_address);
+
+        // less than a frame doesn't run anything, but is carried over
+        chip8.advance(FRAME_DURATION / 2).expect("an error occurred during emulator execution");
+        assert_eq!((chip8.instructions_executed(), chip8.delay_timer), (0, 10), "failed to wait for a full frame");
+
+        chip8.advance(FRAME_DURATION * 2).expect("an error occurred during emulator execution");
+        assert_eq!((chip8.instructions_executed(), chip8.delay_timer), (10, 8), "failed to run 2 frames");
+
+        chip8.advance(FRAME_DURATION / 2).expect("an error occurred during emulator execution");
+        assert_eq!((chip8.instructions_executed(), chip8.delay_timer), (15, 7), "failed to carry over the partial frames");
+
+        // instructions are derived from the time instead of the frames at the normal clock speed
+        chip8.set_pacing_mode(PacingMode::ClockSpeed);
+        chip8.advance(INSTRUCTION_EXEC_DURATION * 20).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.instructions_executed(), 35, "failed to run the instructions for the elapsed time");
+    }
+
+    #[test]
+    fn eti_660_program_start() {
+        let mut chip8 = Chip8::with_memory_layout(DEFAULT_MEMORY_SIZE, ETI_660_PROGRAM_START_ADDRESS, Quirks::default());
+
+        // V0 = 5; jump over the next instruction (absolute address); V0 = 6; V1 = 7
+        let program_data = opcodes_to_bytes(&[0x6005, 0x1606, 0x6006, 0x6107]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.memory[0x600], 0x60, "failed to load the program at the alternate start address");
+        assert_eq!(chip8.registers[0], 5, "failed to correctly execute the program; V0: {}", chip8.registers[0]);
+        assert_eq!(chip8.registers[1], 7, "failed to correctly execute the program; V1: {}", chip8.registers[1]);
+
+        chip8.reset();
+        assert_eq!(chip8.program_counter, ETI_660_PROGRAM_START_ADDRESS, "failed to reset the program counter to the alternate start address");
+    }
+
+    #[test]
+    fn cycles_per_frame() {
+        let mut chip8 = init_emulator();
+        chip8.set_cycles_per_frame(11);
+
+        // endless loop
+        chip8.load_opcode_into_memory(0x1200, chip8.program_start_address);
+
+        // a long frame must not run more instructions, an empty one not less
+        chip8.run_frame(Duration::from_secs(1)).expect("failed to run the frame");
+        assert_eq!(chip8.instructions_executed, 11, "failed to correctly limit the instructions per frame");
+
+        chip8.run_frame(Duration::new(0, 0)).expect("failed to run the frame");
+        assert_eq!(chip8.instructions_executed, 22, "failed to correctly run the instructions per frame");
+
+        // verify result
+        assert_eq!(chip8.get_pacing_mode(), PacingMode::CyclesPerFrame(11), "failed to report the pacing mode");
+    }
+
+    #[test]
+    fn jump_to_odd_address() {
+        let mut chip8 = init_emulator();
+
+        // jump to 0x203, skipping a padding byte; V0 = 42
+        let program_data: Vec<u8> = vec!(0x12, 0x03, 0xFF, 0x60, 0x2A, 0x00, 0x00);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], 42, "failed to correctly execute the instruction at the odd address; V0: {}", chip8.registers[0]);
+        assert_eq!(chip8.program_counter, 0x207, "failed to keep the program counter misaligned; PC: 0x{:04x}", chip8.program_counter);
+    }
+
+    #[test]
+    fn self_modifying_code() {
+        let mut chip8 = init_emulator();
+
+        // dump 0x6209 (V2 = 9) over the halt instruction at 0x20A
+        let program_data = opcodes_to_bytes(&[0x6062, 0x6109, 0xA20A, 0xF155, 0x6300, 0x0000, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[2], 9, "failed to execute the modified instruction; V2: {}", chip8.registers[2]);
+        assert_eq!(chip8.program_counter, 0x20E, "failed to continue after the modified instruction; PC: 0x{:04x}", chip8.program_counter);
+    }
+
+    #[test]
+    fn load_and_run() {
+        let mut chip8 = init_emulator();
+
+        // wait for a key and draw its font sprite at (0, 0)
+        let program_data = opcodes_to_bytes(&[0xF00A, 0xF029, 0xD125, 0x0000]);
+
+        let mut requested_frames = 0;
+        let frame = chip8.load_and_run(&program_data, 100, |frame_idx| {
+            requested_frames += 1;
+
+            let mut keypad = Keypad::new();
+            if frame_idx == 3 {
+                keypad.set_key(0x1);
+            }
+            return keypad;
+        }).expect("failed to run the program");
+
+        // verify result ("1" = 0x20, 0x60, 0x20, 0x20, 0x70)
+        assert!(chip8.is_halted(), "failed to run the program until the halt");
+        assert_eq!(requested_frames, 4, "failed to stop early on the halt");
+        assert!(frame.get_pixel(2, 0) && frame.get_pixel(1, 1) && !frame.get_pixel(0, 0), "failed to return the final frame");
+    }
+
+    #[test]
+    fn cycle_accurate_costs() {
+        let mut chip8 = init_emulator();
+        chip8.set_cycle_accurate(true);
+
+        assert!(chip8.get_instruction_cost(0xD015) > chip8.get_instruction_cost(0x7001), "failed to charge the draw more than the addition");
+
+        // budget for 4 uniform instructions: 3 additions fit, the draw after them doesn't
+        chip8.load_opcodes_into_memory(&vec!(0x7001, 0x7001, 0x7001, 0xD015), chip8.program_start_address);
+        chip8.run_frame(INSTRUCTION_EXEC_DURATION * 4).expect("failed to run the frame");
+
+        // verify result
+        assert_eq!(chip8.instructions_executed, 3, "failed to correctly charge the instruction costs");
+        assert_eq!(chip8.registers[0], 3, "failed to correctly execute the additions; V0: {}", chip8.registers[0]);
+    }
+
+    #[test]
+    fn custom_instruction_costs() {
+        let mut chip8 = init_emulator();
+        let budget = INSTRUCTION_EXEC_DURATION * 12;
+
+        // uniform costs: 12 instructions of the loop (draw, jump back) fit the budget
+        chip8.load_program(&[0xD0, 0x01, 0x12, 0x00]).expect("failed to load the program");
+        chip8.run_frame(budget).expect("failed to run the frame");
+        assert_eq!(chip8.instructions_executed, 12, "failed to charge uniform costs by default");
+
+        // expensive draws (5) and cheap jumps (1): 2 loop iterations of 6 each
+        let mut costs = UNIFORM_INSTRUCTION_COSTS;
+        costs[0xD] = 5;
+        chip8.reset();
+        chip8.set_instruction_costs(costs);
+        chip8.run_frame(budget).expect("failed to run the frame");
+
+        // verify result
+        assert_eq!(chip8.get_instruction_costs(), costs, "failed to set the instruction costs");
+        assert_eq!(chip8.instructions_executed, 4, "failed to charge the draws their configured cost");
+    }
+
+    #[test]
+    fn screen_snapshot_round_trip() {
+        let mut chip8 = init_emulator();
+
+        // draw the font sprite of "0", take a snapshot, then draw over it
+        chip8.load_opcodes_into_memory(&vec!(0xA050, 0xD005, 0x6008, 0xD005), chip8.program_start_address);
+        chip8.run_instructions(2).expect("failed to draw the sprite");
+
+        let snapshot = chip8.screen_snapshot();
+        let frame_after_first_draw = chip8.frame().clone();
+
+        run_emulator(&mut chip8);
+        assert_ne!(chip8.frame(), &frame_after_first_draw, "failed to draw the second sprite");
+
+        chip8.restore_screen(&snapshot);
+
+        // verify result
+        assert_eq!(chip8.frame(), &frame_after_first_draw, "failed to restore the screen");
+        assert_eq!(snapshot.frame(), &frame_after_first_draw, "failed to capture the screen");
+    }
+
+    #[test]
+    fn count_instructions_and_frames() {
+        let mut chip8 = init_emulator();
+
+        // V0 = 60; DT = V0; wait until DT = 0 (V1 = DT; skip if V1 = 0; jump back), then halt
+        let program_data = opcodes_to_bytes(&[0x603C, 0xF015, 0xF107, 0x3100, 0x1204, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+
+        chip8.run_instructions(2).expect("failed to run the instructions");
+        assert_eq!(chip8.instructions_executed(), 2, "failed to count the executed instructions");
+
+        let frame_duration = Duration::from_nanos(1_000_000_000 / 60);
+        for _ in 0..60 {
+            chip8.run_frame(frame_duration).expect("failed to run the frame");
+        }
+
+        // verify result
+        assert_eq!(chip8.frames_run(), 60, "failed to count the frames");
+        assert_eq!(chip8.get_delay_timer(), 0, "failed to count down the delay timer within 60 frames");
+
+        chip8.reset();
+        assert_eq!(chip8.instructions_executed(), 0, "failed to reset the instruction counter");
+        assert_eq!(chip8.frames_run(), 0, "failed to reset the frame counter");
+    }
+
+    #[test]
+    fn count_program_instructions() {
+        let mut chip8 = init_emulator();
+
+        // 4 of the 5 instructions are executed (the second one skips the third one)
+        let program_data = opcodes_to_bytes(&[0x6001, 0x3001, 0x6002, 0x7001, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.instructions_executed(), 4, "failed to count the executed instructions of the program");
+    }
+
+    #[test]
+    fn breakpoint_hit() {
+        let mut chip8 = init_emulator();
+
+        let program_data = opcodes_to_bytes(&[0x6001, 0x6102, 0x6203, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        chip8.add_breakpoint(0x204);
+
+        let outcome = chip8.run_instructions(10).expect("failed to run the program");
+        assert_eq!(outcome, RunOutcome::BreakpointHit(0x204), "failed to stop at the breakpoint");
+        assert_eq!(chip8.registers[2], 0, "failed to stop before executing the instruction at the breakpoint");
+
+        // running again continues past the breakpoint
+        let outcome = chip8.run_instructions(10).expect("failed to run the program");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::Halted, "failed to resume after the breakpoint");
+        assert_eq!(chip8.registers[2], 3, "failed to execute the instruction at the breakpoint; V2: {}", chip8.registers[2]);
+    }
+
+    #[test]
+    fn conditional_breakpoint_hit() {
+        let mut chip8 = init_emulator();
+        // count V0 up from 0 to 5 in a loop (V0 += 1 at 0x200; jump back unless V0 = 5), then halt
+        let program_data = opcodes_to_bytes(&[0x7001, 0x3005, 0x1200, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        chip8.add_conditional_breakpoint(0x202, 0, 3);
+
+        // the loop passes the breakpoint twice before V0 is 3
+        let outcome = chip8.run_instructions(100).expect("an error occurred during emulator execution");
+        assert_eq!(outcome, RunOutcome::BreakpointHit(0x202), "failed to stop once the condition is met");
+        assert_eq!(chip8.registers[0], 3, "failed to ignore the breakpoint while the condition isn't met");
+        assert_eq!(chip8.instructions_executed(), 7, "failed to pass the breakpoint while the condition isn't met");
+
+        // the condition isn't met anymore on later passes
+        let outcome = chip8.run_instructions(100).expect("an error occurred during emulator execution");
+        assert_eq!(outcome, RunOutcome::Halted, "failed to resume after the conditional breakpoint");
+        assert_eq!(chip8.registers[0], 5, "failed to finish the loop; V0: {}", chip8.registers[0]);
+    }
+
+    #[test]
+    fn watchpoint_hit() {
+        let mut chip8 = init_emulator();
+
+        // V0 = 123; I = 0x300; BCD of V0 (writes 0x300 - 0x302); V1 = 1
+        let program_data = opcodes_to_bytes(&[0x607B, 0xA300, 0xF033, 0x6101, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        chip8.add_watchpoint(0x301);
+
+        let outcome = chip8.run_instructions(10).expect("failed to run the program");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::WatchpointHit(0x301), "failed to stop at the watchpoint");
+        assert_eq!(chip8.memory[0x301], 2, "failed to stop after the write");
+        assert_eq!(chip8.registers[1], 0, "failed to stop right after the instruction that wrote to the address");
+    }
+
+    #[test]
+    fn reached_end_of_file_outcome() {
+        let mut chip8 = init_emulator();
+
+        // no halt instruction in between: jump to the last address
+        chip8.load_opcode_into_memory(0x1FFE, chip8.program_start_address);
+        chip8.load_opcode_into_memory(0x6001, 0xFFE);
+
+        let outcome = chip8.run_instructions(10).expect("failed to run the program");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::ReachedEndOfFile, "failed to report the end of memory");
+        assert!(chip8.is_halted(), "failed to halt at the end of memory");
+    }
+
+    #[test]
+    fn load_program_after_another() {
+        let mut chip8 = init_emulator();
+        // V5 = 0xAA; I = 0x300; dump V0 - V5; CALL 0x20C; draw glyph 0 (in the subroutine), then halt
+        let first_program = opcodes_to_bytes(&[0x65AA, 0xA300, 0xF555, 0x6B05, 0xFB18, 0x220C, 0xF029, 0xD005, 0x0000]);
+        chip8.load_program(&first_program).expect("failed to load the first program");
+        run_emulator(&mut chip8);
+
+        // a shorter program, so the tail of the first one would still be in memory without a reset
+        let second_program = opcodes_to_bytes(&[0x6101, 0x0000]);
+        chip8.load_program(&second_program).expect("failed to load the second program");
+
+        // verify result
+        assert_eq!(chip8.get_program_counter(), DEFAULT_PROGRAM_START_ADDRESS, "failed to reset the program counter");
+        assert_eq!(chip8.registers, [0; 16], "failed to clear the registers");
+        assert_eq!(chip8.get_index_reg(), 0, "failed to clear I");
+        assert_eq!(chip8.get_stack_depth(), 0, "failed to clear the stack");
+        assert_eq!(chip8.get_sound_timer(), 0, "failed to clear the sound timer");
+        assert!(!chip8.is_halted(), "failed to resume execution");
+        assert!(chip8.frame().pixels().iter().all(|pixel| !pixel), "failed to clear the screen");
+        assert!(chip8.memory[0x300..0x306].iter().all(|byte| *byte == 0), "failed to clear the dumped registers");
+        assert!(chip8.memory[0x204..0x212].iter().all(|byte| *byte == 0), "failed to clear the rest of the first program");
+        assert_eq!(chip8.get_rom_data(), second_program.as_slice(), "failed to keep the second program");
+
+        run_emulator(&mut chip8);
+        assert_eq!(chip8.registers[1], 1, "failed to run the second program");
+    }
+
+    #[test]
+    fn peek_next_instruction() {
+        let mut chip8 = init_emulator();
+        chip8.load_opcodes_into_memory(&vec!(0x6005, 0x1204), DEFAULT_PROGRAM_START_ADDRESS);
+
+        assert_eq!(chip8.peek_next_instruction(), Some((0x200, 0x6005)), "failed to return the first instruction");
+        assert_eq!(chip8.get_program_counter(), 0x200, "falsely advanced the program counter");
+        assert_eq!(chip8.get_register(0), 0, "falsely executed the instruction");
+
+        chip8.exec_next_instruction().expect("an error occurred during emulator execution");
+        assert_eq!(chip8.peek_next_instruction(), Some((0x202, 0x1204)), "failed to return the second instruction");
+
+        // only a single byte is left before the end of memory
+        chip8.program_counter = (DEFAULT_MEMORY_SIZE - 1) as u16;
+        assert_eq!(chip8.peek_next_instruction(), None, "failed to report that no instruction is left");
+    }
+
+    #[test]
+    fn peek_opcode() {
+        let mut chip8 = init_emulator();
+        chip8.load_opcodes_into_memory(&vec!(0x6005), DEFAULT_PROGRAM_START_ADDRESS);
+
+        assert_eq!(chip8.peek_opcode(), 0x6005, "failed to return the opcode at the program counter");
+        assert_eq!(chip8.get_program_counter(), 0x200, "falsely advanced the program counter");
+
+        chip8.program_counter = (DEFAULT_MEMORY_SIZE - 1) as u16;
+        assert_eq!(chip8.peek_opcode(), 0x0000, "failed to return 0x0000 at the end of memory");
+    }
+
+    #[test]
+    fn load_audio_pattern() {
+        let mut chip8 = init_emulator();
+        let pattern: Vec<u8> = (0..16).map(|idx| idx * 0x11).collect();
+        // I = pattern; load the pattern; V0 = 112; pitch = V0
+        chip8.load_program(&opcodes_to_bytes(&[0xA300, 0xF002, 0x6070, 0xF03A, 0x0000])).expect("failed to load the program");
+        chip8.load_bytes_into_memory(&pattern, 0x300);
+
+        assert_eq!(chip8.get_audio_pattern(), None, "falsely reported a pattern before loading one");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.get_audio_pattern().map(|pattern| pattern.to_vec()), Some(pattern), "failed to load the audio pattern");
+        assert_eq!(chip8.get_pitch(), 112, "failed to set the pitch");
+        assert_eq!(chip8.get_audio_pattern_rate(), 8000.0, "failed to double the rate 48 steps above the default pitch");
+    }
+
+    #[test]
+    fn select_planes() {
+        let mut chip8 = init_emulator();
+        // I = glyph "0"; select the second plane; draw 1 row; select both planes; draw 1 row per plane at (8, 0)
+        let program_data = opcodes_to_bytes(&[0xA050, 0xF201, 0xD001, 0x6108, 0xF301, 0xD101, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        // verify result (the upper 2 rows of "0" are 0xF0 and 0x90)
+        assert!(chip8.second_plane().get_pixel(0, 0), "failed to draw on the second plane");
+        assert!(!chip8.frame().get_pixel(0, 0), "falsely drew on the first plane");
+        assert!(chip8.frame().get_pixel(9, 0), "failed to draw the first row on the first plane");
+        assert!(chip8.second_plane().get_pixel(8, 0) && !chip8.second_plane().get_pixel(9, 0), "failed to draw the second row on the second plane");
+
+        chip8.reset();
+        assert_eq!(chip8.screen.get_selected_planes(), screen::DEFAULT_SELECTED_PLANES, "failed to reset the plane selection");
+        assert!(!chip8.second_plane().get_pixel(0, 0), "failed to clear the second plane on reset");
+    }
+
+    #[test]
+    fn frame_version() {
+        let mut chip8 = init_emulator();
+        // CLS (empty screen); V0 = 1; draw glyph 1; V0 = 2 (no drawing); CLS; CLS (empty screen)
+        let program_data = opcodes_to_bytes(&[0x00E0, 0x6001, 0xF029, 0xD005, 0x6002, 0x00E0, 0x00E0, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+
+        // (instructions to run, expected change of the version)
+        let steps = [(3, 0), (1, 1), (1, 0), (1, 1), (1, 0)];
+        for (count, expected_change) in steps {
+            let version = chip8.frame_version();
+            let snapshot = chip8.snapshot_frame();
+            chip8.run_instructions(count).expect("an error occurred during emulator execution");
+
+            assert_eq!(chip8.frame_version() - version, expected_change, "failed to update the version at 0x{:04x}", chip8.get_program_counter());
+            assert_eq!(chip8.snapshot_frame() == snapshot, expected_change == 0, "failed to change the version only with the frame buffer at 0x{:04x}", chip8.get_program_counter());
+        }
+    }
+
+    #[test]
+    fn cap_instructions_per_frame() {
+        let mut chip8 = init_emulator();
+        chip8.set_max_instructions_per_frame(1000);
+        // endless loop
+        chip8.load_program(&opcodes_to_bytes(&[0x1200])).expect("failed to load the program");
+
+        // e.g. after the application was suspended for an hour
+        chip8.run_frame(Duration::from_secs(3600)).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.instructions_executed(), 1000, "failed to cap the instructions of the frame");
+
+        // the excess time is discarded
+        chip8.run_frame(FRAME_DURATION).expect("an error occurred during emulator execution");
+        assert!(chip8.instructions_executed() - 1000 <= 12, "failed to return to the normal speed; instructions: {}", chip8.instructions_executed() - 1000);
+
+        // a fixed number of cycles per frame ignores the frame duration, but the frames of a long advance are capped
+        chip8.set_cycles_per_frame(10);
+        let start_instructions = chip8.instructions_executed();
+        chip8.run_frame(Duration::from_secs(3600)).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.instructions_executed() - start_instructions, 10, "failed to run exactly the cycles of the frame");
+
+        let start_instructions = chip8.instructions_executed();
+        chip8.advance(Duration::from_secs(3600)).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.instructions_executed() - start_instructions, 1000, "failed to cap the instructions of the advanced frames");
+
+        // more cycles per frame than the cap
+        chip8.set_cycles_per_frame(5000);
+        let start_instructions = chip8.instructions_executed();
+        chip8.run_frame(FRAME_DURATION).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.instructions_executed() - start_instructions, 1000, "failed to cap the cycles of the frame");
+    }
+
+    #[test]
+    fn detect_code_writes() {
+        // a 10 byte program: V0 = V1 = 0; I = 0x202; dump V0 - V1 to 0x202 (overwrites `V1 = 0` with itself); halt
+        let program_data = opcodes_to_bytes(&[0x6000, 0x6100, 0xA202, 0xF155, 0x0000]);
+
+        for detect_code_writes in [false, true] {
+            let mut chip8 = init_emulator();
+            chip8.set_detect_code_writes(detect_code_writes);
+            chip8.load_program(&program_data).expect("failed to load the program");
+            run_emulator(&mut chip8);
+
+            let expected = if detect_code_writes { Some(0x203) } else { None };
+            assert_eq!(chip8.last_code_write(), expected, "failed to detect the write into the code (detection enabled: {})", detect_code_writes);
+        }
+
+        // writes behind the program aren't reported
+        let mut chip8 = init_emulator();
+        chip8.set_detect_code_writes(true);
+        chip8.load_program(&opcodes_to_bytes(&[0xA20A, 0xF155, 0x0000])).expect("failed to load the program");
+        run_emulator(&mut chip8);
+
+        assert_eq!(chip8.last_code_write(), None, "falsely reported a write behind the program");
+    }
+
+    #[test]
+    fn loaded_rom_checksum() {
+        let mut chip8 = init_emulator();
+
+        let program_data = opcodes_to_bytes(&[0x6005, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+
+        // verify result
+        assert_eq!(chip8.loaded_rom_checksum(), 0x1CA9EAF4, "failed to compute a stable checksum of the program");
+    }
+
+    #[test]
+    fn set_and_get_register() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 0x2A;
+
+        chip8.set_register(0xE, val_1);
+
+        // verify result
+        assert_eq!(chip8.get_register(0xE), val_1, "failed to round-trip the value through the register; value: {}, reg: {}", val_1, chip8.get_register(0xE));
+        assert_eq!(chip8.get_register(0xD), 0, "failed to leave the other registers untouched");
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_register_with_invalid_id() {
+        let mut chip8 = init_emulator();
+
+        chip8.set_register(0x10, 1);
+    }
+
+    #[test]
+    fn load_hex_string() {
+        let mut chip8 = init_emulator();
+
+        // load opcodes
+        let hex = "6005, 6107 ; V0 = 5, V1 = 7\n0x8014 ; V0 += V1";
+        chip8.load_hex_string(hex, chip8.program_start_address).expect("failed to load the hex string");
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], 12, "failed to correctly execute the loaded opcodes; V0: {}", chip8.registers[0]);
+    }
+
+    #[test]
+    fn load_hex_string_with_invalid_token() {
+        let mut chip8 = init_emulator();
+
+        let result = chip8.load_hex_string("00E0 A2ZA 600C", chip8.program_start_address);
+
+        assert!(matches!(result, Err(Chip8Error::InvalidHexString(_))), "failed to reject the invalid token");
+    }
+
+    #[test]
+    fn add_xy() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+        let val_2 = 7;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8014, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1 + val_2, "failed to correctly add the two registers; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 0, "failed to correctly set the carry bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn add_xy_with_carry() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 1;
+        let val_2 = 255;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8014, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], 0, "failed to correctly add the two registers; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 1, "failed to correctly set the carry bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn add_const_to_x() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+        let val_2 = 7;
+
+        // load registers
+        chip8.set_register(0, val_1);
+
+        // load opcodes
+        let opcode: u16 = (0x7000 as u16) | (val_2 as u16);
+        chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1 + val_2, "failed to correctly add a constant and a register; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+    }
+
+    #[test]
+    fn subtract_y_from_x() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 8;
+        let val_2 = 3;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8015, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1 - val_2, "failed to correctly subtract the two registers (result = a - b); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 1, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn subtract_y_from_x_with_underflow() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 8;
+        let val_2 = 10;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8015, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], 254, "failed to correctly subtract the two registers (result = a - b); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 0, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn subtract_x_from_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 3;
+        let val_2 = 8;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8017, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_2 - val_1, "failed to correctly subtract the two registers (result = b - a); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 1, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn subtract_x_from_y_with_underflow() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 10;
+        let val_2 = 8;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8017, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], 254, "failed to correctly subtract the two registers (result = b - a); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 0, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn assign_const_to_x() {
+        let mut chip8 = init_emulator();
+
+        let val_1: u8 = 0x15;
+
+        // load opcodes
+        let opcode: u16 = (0x6000 as u16) | (val_1 as u16);
+        chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly assign constant to register; constant: {}, reg: {}", val_1, chip8.registers[0]);
+    }
+
+    #[test]
+    fn assign_y_to_x() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 10;
+
+        // load registers
+        chip8.set_register(1, val_1);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8010, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly assign register y to register x; reg_y: {}, reg_x: {}", val_1, chip8.registers[0]);
+    }
+
+    #[test]
+    fn bitwise_or_x_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 10;
+        let val_2 = 15;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8011, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], (val_1 | val_2), "failed to correctly perform the bitwise OR operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
+    }
+
+    #[test]
+    fn bitwise_and_x_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 64;
+        let val_2 = 15;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8012, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], (val_1 & val_2), "failed to correctly perform the bitwise AND operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
+    }
+
+    #[test]
+    fn bitwise_xor_x_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 65;
+        let val_2 = 15;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x8013, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], (val_1 ^ val_2), "failed to correctly perform the bitwise XOR operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
     }
 
     #[test]
-    fn add_xy_with_carry() {
+    fn right_bit_shift() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 1;
-        let val_2 = 255;
+        let val_1 = 65;
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        chip8.set_register(1, val_1);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0x8014, PROGRAM_START_ADDRESS);
+        chip8.load_opcode_into_memory(0x8016, chip8.program_start_address);
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], 0, "failed to correctly add the two registers; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        assert_eq!(chip8.registers[0], val_1 >> 1, "failed to correctly perform the right bit-shift operation; val_1: {}, result: {}", val_1, chip8.registers[0]);
 
         let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 1, "failed to correctly set the carry bit; VF register: 0x{:02x}", vf_register);
+        assert_eq!(*vf_register, 1, "failed to correctly load the LSB into VF; VF register: 0x{:02x}", vf_register);
     }
 
     #[test]
-    fn add_const_to_x() {
+    fn left_bit_shift() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 255;
+
+        // load registers
+        chip8.set_register(1, val_1);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x801E, chip8.program_start_address);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1 << 1, "failed to correctly perform the left bit-shift operation; val_1: {}, result: {}", val_1, chip8.registers[0]);
+
+        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(*vf_register, 1, "failed to correctly load the LSB into VF; VF register: 0x{:02x}", vf_register);
+    }
+
+    #[test]
+    fn flag_register_as_destination() {
+        // (opcode, VF, V1, expected VF)
+        let cases = [
+            (0x8F14, 0xFF, 0x02, 1), // ADD with carry
+            (0x8F14, 0x01, 0x02, 0), // ADD without carry
+            (0x8F15, 0x01, 0x02, 0), // SUB with borrow
+            (0x8F15, 0x05, 0x02, 1), // SUB without borrow
+            (0x8F17, 0x05, 0x02, 0), // SUBN with borrow
+            (0x8F17, 0x02, 0x05, 1), // SUBN without borrow
+            (0x8F16, 0x00, 0x03, 1), // SHR
+            (0x8F1E, 0x00, 0x40, 0), // SHL
+        ];
+
+        for (opcode, vf_val, y_val, expected_flag) in cases {
+            let mut chip8 = init_emulator();
+
+            // load registers
+            chip8.set_register(FLAG_REG_ID, vf_val);
+            chip8.set_register(1, y_val);
+
+            // load opcodes
+            chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+            run_emulator(&mut chip8);
+
+            // verify result
+            let vf_register = chip8.registers[FLAG_REG_ID as usize];
+            assert_eq!(vf_register, expected_flag, "failed to correctly set the flag when VF is the destination; opcode: 0x{:04x}, VF register: 0x{:02x}", opcode, vf_register);
+        }
+    }
+
+    #[test]
+    fn shift_flag_register_in_place() {
+        // (opcode, VF, expected VF): the shifted-out bit differs from the shifted value in every case
+        let cases = [
+            (0x8FF6, 0x05, 1), // SHR (result 0x02)
+            (0x8FF6, 0x02, 0), // SHR (result 0x01)
+            (0x8FFE, 0x81, 1), // SHL (result 0x02)
+            (0x8FFE, 0x40, 0), // SHL (result 0x80)
+        ];
+
+        for (opcode, vf_val, expected_flag) in cases {
+            let mut chip8 = Chip8::new(Quirks { assign_before_shift: false, ..Quirks::default() });
+
+            // load registers
+            chip8.set_register(FLAG_REG_ID, vf_val);
+
+            // load opcodes
+            chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+            run_emulator(&mut chip8);
+
+            // verify result
+            let vf_register = chip8.registers[FLAG_REG_ID as usize];
+            assert_eq!(vf_register, expected_flag, "failed to set VF to the shifted-out bit; opcode: 0x{:04x}, VF register: 0x{:02x}", opcode, vf_register);
+        }
+    }
+
+    #[test]
+    fn skip_if_x_equals_const() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+
+        // load registers
+        chip8.set_register(0, val_1);
+
+        // load opcodes
+        let opcode: u16 = (0x3000 as u16) | (val_1 as u16);
+        chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+        // if the skip fails, V0 is set to 0x11
+        chip8.load_opcode_into_memory(0x6011, chip8.program_start_address + 2);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX == NN) operation");
+    }
+
+    #[test]
+    fn skip_if_x_not_equals_const() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+
+        // load registers
+        chip8.set_register(0, val_1);
+
+        // load opcodes
+        let opcode: u16 = (0x4000 as u16) | ((val_1 + 1) as u16);
+        chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+        // if the skip fails, V0 is set to 0x11
+        chip8.load_opcode_into_memory(0x6011, chip8.program_start_address + 2);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX != NN) operation");
+    }
+
+    #[test]
+    fn skip_if_x_equals_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_1);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x5010, chip8.program_start_address);
+        // if the skip fails, V0 is set to 0x11
+        chip8.load_opcode_into_memory(0x6011, chip8.program_start_address + 2);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX == VY) operation");
+    }
+
+    #[test]
+    fn skip_if_x_not_equals_y() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_1 + 1);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x9010, chip8.program_start_address);
+        // if the skip fails, V0 is set to 0x11
+        chip8.load_opcode_into_memory(0x6011, chip8.program_start_address + 2);
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX != VY) operation");
+    }
+
+    #[test]
+    fn call_and_return_from_subroutine() {
         let mut chip8 = init_emulator();
 
         let val_1 = 5;
         let val_2 = 7;
 
         // load registers
-        chip8.load_register(0, val_1);
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
 
         // load opcodes
-        let opcode: u16 = (0x7000 as u16) | (val_2 as u16);
-        chip8.load_opcode_into_memory(opcode, PROGRAM_START_ADDRESS);
+        let main_opcodes: Vec<u16> = vec!(0x2300, 0x8014);
+        chip8.load_opcodes_into_memory(&main_opcodes, chip8.program_start_address);
+
+        let subroutine_opcodes: Vec<u16> = vec!(0x8104, 0x00EE);
+        chip8.load_opcodes_into_memory(&subroutine_opcodes, 0x300);
+
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1 + val_2, "failed to correctly add a constant and a register; a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly call subroutine");
+
+        assert_eq!(chip8.registers[0], val_1 * 2 + val_2, "failed to correctly return from subroutine");
     }
 
     #[test]
-    fn subtract_y_from_x() {
+    fn jump_to_address() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 8;
-        let val_2 = 3;
+        let val_1 = 5;
+        let val_2 = 7;
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0x8015, PROGRAM_START_ADDRESS);
+        chip8.load_opcode_into_memory(0x2300, chip8.program_start_address);
+        chip8.load_opcode_into_memory(0x8104, 0x300);
+
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1 - val_2, "failed to correctly subtract the two registers (result = a - b); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly execute jump");
+    }
+
+    #[test]
+    fn jump_to_address_with_displacement() {
+        let mut chip8 = init_emulator();
+
+        let val_1 = 5;
+        let val_2 = 7;
+
+        // load registers
+        chip8.set_register(0, val_1);
+        chip8.set_register(1, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0xB2FB, chip8.program_start_address);
+        chip8.load_opcode_into_memory(0x8104, 0x300);
+
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly execute jump");
+    }
+
+    #[test]
+    fn jump_to_address_with_large_displacement() {
+        let mut chip8 = init_emulator();
+
+        // load registers
+        chip8.set_register(0, 0xFF);
+
+        // load opcodes (0x0FFF + 0xFF wraps around to 0x00FE)
+        chip8.load_opcode_into_memory(0xBFFF, chip8.program_start_address);
+        chip8.load_opcode_into_memory(0x6111, 0x00FE);
+        chip8.load_opcode_into_memory(0x0000, 0x0100); // halt instead of running into the big font
+
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.registers[1], 0x11, "failed to correctly wrap the jump around the end of memory");
+    }
+
+    #[test]
+    fn return_from_subroutine_with_empty_stack() {
+        let mut chip8 = init_emulator();
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0x00EE, chip8.program_start_address);
+
+        let result = chip8.exec_next_instruction();
+
+        // verify result
+        assert!(matches!(result, Err(Chip8Error::StackUnderflow(_))), "failed to report the stack underflow");
+    }
+
+    #[test]
+    fn instruction_limit() {
+        let mut chip8 = init_emulator();
+        chip8.set_max_instructions(Some(100));
+
+        // load opcodes (infinite loop)
+        chip8.load_opcode_into_memory(0x1200, chip8.program_start_address);
+
+        let outcome = chip8.run_instructions(1000).expect("an error occurred during emulator execution");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::InstructionLimitReached, "failed to stop at the instruction limit");
+        assert_eq!(chip8.instructions_executed, 100, "failed to execute exactly up to the instruction limit");
+    }
+
+    #[test]
+    fn step_over_subroutine() {
+        let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
+
+        // load opcodes (call a subroutine that sets V0 and returns)
+        chip8.load_opcode_into_memory(0x2000 | (start_address + 4), start_address);
+        chip8.load_opcode_into_memory(0x6101, start_address + 2);
+        chip8.load_opcode_into_memory(0x6005, start_address + 4);
+        chip8.load_opcode_into_memory(0x00EE, start_address + 6);
+
+        let outcome = chip8.step_over().expect("an error occurred during emulator execution");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::Continue, "failed to hide the temporary breakpoint");
+        assert_eq!(chip8.program_counter, start_address + 2, "failed to stop at the instruction after the call");
+        assert_eq!(chip8.registers[0], 0x05, "failed to run the subroutine");
+        assert!(chip8.breakpoints.is_empty(), "failed to remove the temporary breakpoint");
+
+        // not a call -> behaves like step()
+        chip8.step_over().expect("an error occurred during emulator execution");
+        assert_eq!(chip8.registers[1], 0x01, "failed to step over a regular instruction");
+        assert_eq!(chip8.program_counter, start_address + 4, "failed to execute exactly one instruction");
+    }
+
+    #[test]
+    fn step_over_endless_subroutine() {
+        let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
+        chip8.set_max_instructions(Some(100));
+
+        // load opcodes (call a subroutine that never returns)
+        chip8.load_opcode_into_memory(0x2000 | (start_address + 2), start_address);
+        chip8.load_opcode_into_memory(0x1000 | (start_address + 2), start_address + 2);
+
+        let outcome = chip8.step_over().expect("an error occurred during emulator execution");
+
+        // verify result
+        assert_eq!(outcome, RunOutcome::InstructionLimitReached, "failed to stop at the instruction limit");
+
+        // without an instruction limit, it gives up after the step over cap
+        let mut chip8 = init_emulator();
+        chip8.load_opcode_into_memory(0x2000 | (start_address + 2), start_address);
+        chip8.load_opcode_into_memory(0x1000 | (start_address + 2), start_address + 2);
+
+        let outcome = chip8.step_over().expect("an error occurred during emulator execution");
+        assert_eq!(outcome, RunOutcome::Continue, "failed to stop inside the subroutine");
+        assert_eq!(chip8.instructions_executed, STEP_OVER_MAX_INSTRUCTIONS, "failed to stop at the step over cap");
+        assert!(chip8.breakpoints.is_empty(), "failed to remove the temporary breakpoint");
+    }
+
+    #[test]
+    fn recent_instructions_before_error() {
+        let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
+        chip8.set_recent_instructions_capacity(2);
+
+        // load opcodes (the last one is invalid)
+        chip8.load_opcode_into_memory(0x6001, start_address);
+        chip8.load_opcode_into_memory(0x6102, start_address + 2);
+        chip8.load_opcode_into_memory(0x6203, start_address + 4);
+        chip8.load_opcode_into_memory(0x8FFF, start_address + 6);
+
+        let result = chip8.run_instructions(4);
+
+        // verify result
+        assert!(result.is_err(), "failed to report the invalid instruction");
+        assert_eq!(chip8.recent_instructions(), vec!((start_address + 4, 0x6203), (start_address + 6, 0x8FFF)), "failed to keep the last instructions up to the error");
+    }
+
+    #[test]
+    fn recover_from_error() {
+        let mut chip8 = init_emulator();
+        chip8.set_cycles_per_frame(10);
+
+        // the program sets V0 and then runs into an unimplemented instruction
+        chip8.load_program(&opcodes_to_bytes(&[0x6005, 0x5001])).expect("failed to load the program");
+
+        let result = chip8.run_frame(FRAME_DURATION);
+
+        // verify result
+        assert!(matches!(result, Err(Chip8Error::UnknownOpcode { .. })), "failed to report the unimplemented instruction");
+        assert!(!chip8.is_halted(), "failed to keep the emulator from halting on an error");
+        assert_eq!(chip8.recent_instructions().last(), Some(&(chip8.program_start_address + 2, 0x5001)), "failed to record the failed instruction");
+
+        chip8.reset();
+        assert_eq!(chip8.registers[0], 0, "failed to reset the registers");
+
+        chip8.run_instructions(1).expect("an error occurred during emulator execution");
+        assert_eq!(chip8.registers[0], 5, "failed to run the program again after the reset");
+    }
+
+    #[test]
+    fn set_index_reg() {
+        let mut chip8 = init_emulator();
+
+        let val_1: u16 = 5;
+
+        // load opcodes
+        let opcode: u16 = (0xA000 as u16) | val_1;
+        chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.index_reg, val_1, "failed to correctly set the index register; index_reg: {}", chip8.index_reg);
+    }
+
+    #[test]
+    fn add_x_to_index() {
+        let mut chip8 = init_emulator();
+
+        let val_1: u16 = 5;
+        let val_2: u8 = 7;
+
+        // load registers
+        chip8.load_index_reg(val_1);
+        chip8.set_register(0, val_2);
+
+        // load opcodes
+        chip8.load_opcode_into_memory(0xF01E, chip8.program_start_address);
+
+        run_emulator(&mut chip8);
+
+        // verify result
+        assert_eq!(chip8.index_reg, val_1 + (val_2 as u16), "failed to correctly add to X to index register; index_reg: {}", chip8.index_reg);
+    }
+
+    #[test]
+    fn skip_if_key_pressed() {
+        // (VX, expected PC offset)
+        let cases = [(0x5, 4), (0x6, 2), (0xF5, 4), (0xFF, 2)];
+
+        for (key_id, expected_offset) in cases {
+            let mut chip8 = init_emulator();
+
+            let mut keypad = Keypad::new();
+            keypad.set_key(0x5);
+            chip8.load_keypad(&keypad);
+
+            // load registers
+            chip8.set_register(0, key_id);
+
+            // load opcodes
+            chip8.load_opcode_into_memory(0xE09E, chip8.program_start_address);
+            chip8.exec_next_instruction().expect("an error occurred during emulator execution");
 
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 1, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+            // verify result
+            assert_eq!(chip8.program_counter, chip8.program_start_address + expected_offset, "failed to correctly skip if the key is pressed; VX: 0x{:02x}", key_id);
+        }
     }
 
     #[test]
-    fn subtract_y_from_x_with_underflow() {
-        let mut chip8 = init_emulator();
+    fn skip_if_key_not_pressed() {
+        // (VX, expected PC offset)
+        let cases = [(0x5, 2), (0x6, 4), (0xF5, 2), (0xFF, 4)];
 
-        let val_1 = 8;
-        let val_2 = 10;
+        for (key_id, expected_offset) in cases {
+            let mut chip8 = init_emulator();
 
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+            let mut keypad = Keypad::new();
+            keypad.set_key(0x5);
+            chip8.load_keypad(&keypad);
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8015, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+            // load registers
+            chip8.set_register(0, key_id);
 
-        // verify result
-        assert_eq!(chip8.registers[0], 254, "failed to correctly subtract the two registers (result = a - b); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+            // load opcodes
+            chip8.load_opcode_into_memory(0xE0A1, chip8.program_start_address);
+            chip8.exec_next_instruction().expect("an error occurred during emulator execution");
 
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 0, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+            // verify result
+            assert_eq!(chip8.program_counter, chip8.program_start_address + expected_offset, "failed to correctly skip if the key is not pressed; VX: 0x{:02x}", key_id);
+        }
     }
 
     #[test]
-    fn subtract_x_from_y() {
+    fn skip_if_key_tapped() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 3;
-        let val_2 = 8;
+        // the key is pressed and released again before the frame is run
+        let mut keypad = Keypad::new();
+        keypad.set_key(0x5);
+        keypad.unset_key(0x5);
+        chip8.load_keypad(&keypad);
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
-
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8017, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        chip8.set_register(0, 0x5);
 
-        // verify result
-        assert_eq!(chip8.registers[0], val_2 - val_1, "failed to correctly subtract the two registers (result = b - a); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        // load opcodes: the first check sees the tap, the second one doesn't
+        chip8.load_opcodes_into_memory(&vec!(0xE09E, 0x0000, 0xE09E), chip8.program_start_address);
+        chip8.exec_next_instruction().expect("an error occurred during emulator execution");
+        assert_eq!(chip8.program_counter, chip8.program_start_address + 4, "failed to skip because of the tapped key");
 
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 1, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+        chip8.exec_next_instruction().expect("an error occurred during emulator execution");
+        assert_eq!(chip8.program_counter, chip8.program_start_address + 6, "failed to consume the tap");
     }
 
     #[test]
-    fn subtract_x_from_y_with_underflow() {
+    fn set_index_to_char_font() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 10;
-        let val_2 = 8;
+        let val_1: u8 = 0xF;
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        chip8.set_register(0, val_1);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0x8017, PROGRAM_START_ADDRESS);
+        chip8.load_opcode_into_memory(0xF029, chip8.program_start_address);
+
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], 254, "failed to correctly subtract the two registers (result = b - a); a: {}, b: {}, result: {}", val_1, val_2, chip8.registers[0]);
-
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 0, "failed to correctly set the underflow bit; VF register: 0x{:02x}", vf_register);
+        assert_eq!(chip8.index_reg, FONT_START_ADDRESS + (15 * 5), "failed to correctly set the index register to the fonts location; index_reg: 0x{:04x}; character: 0x{:02x}", chip8.index_reg, val_1);
     }
 
     #[test]
-    fn assign_const_to_x() {
+    fn exec_next_instruction_reports_halt() {
         let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
 
-        let val_1: u8 = 0x15;
-
-        // load opcodes
-        let opcode: u16 = (0x6000 as u16) | (val_1 as u16);
-        chip8.load_opcode_into_memory(opcode, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        // load opcodes (set V0, then halt)
+        chip8.load_opcode_into_memory(0x6001, start_address);
+        chip8.load_opcode_into_memory(0x0000, start_address + 2);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly assign constant to register; constant: {}, reg: {}", val_1, chip8.registers[0]);
+        assert!(chip8.exec_next_instruction().expect("an error occurred during emulator execution"), "falsely reported a halt");
+        assert!(!chip8.exec_next_instruction().expect("an error occurred during emulator execution"), "failed to report the halt");
+        assert!(!chip8.exec_next_instruction().expect("an error occurred during emulator execution"), "failed to report the halt");
     }
 
     #[test]
-    fn assign_y_to_x() {
+    fn soft_reset_keeps_program() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 10;
-
-        // load registers
-        chip8.load_register(1, val_1);
-
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8010, PROGRAM_START_ADDRESS);
+        // set V0 and VF, dump them at 0x300 and halt
+        let program_data = opcodes_to_bytes(&[0x6012, 0x6F34, 0xA300, 0xF055, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
         run_emulator(&mut chip8);
+        assert_eq!(chip8.memory[0x300], 0x12, "failed to run the program");
+
+        chip8.soft_reset();
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly assign register y to register x; reg_y: {}, reg_x: {}", val_1, chip8.registers[0]);
+        let start_address = chip8.program_start_address as usize;
+        assert_eq!(&chip8.memory[start_address..(start_address + program_data.len())], program_data.as_slice(), "failed to keep the program in memory");
+        assert_eq!(chip8.memory[FONT_START_ADDRESS as usize], DEFAULT_FONT_DATA[0], "failed to keep the font in memory");
+        assert_eq!(chip8.memory[0x300], 0x00, "failed to clear the memory written by the program");
+        assert_eq!((chip8.registers[0x0], chip8.registers[0xF], chip8.index_reg), (0, 0, 0), "failed to reset the registers");
+        assert_eq!(chip8.get_program_counter(), chip8.program_start_address, "failed to reset the program counter");
+        assert!(!chip8.is_halted(), "failed to resume the emulator");
     }
 
     #[test]
-    fn bitwise_or_x_y() {
+    fn flag_hook() {
         let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
 
-        let val_1 = 10;
-        let val_2 = 15;
-
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        let flag_changes: Rc<RefCell<Vec<FlagChange>>> = Rc::new(RefCell::new(vec!()));
+        let recorded_changes = Rc::clone(&flag_changes);
+        chip8.set_flag_hook(Some(Box::new(move |flag_change| recorded_changes.borrow_mut().push(flag_change))));
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8011, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        // load opcodes (add with carry)
+        chip8.load_opcode_into_memory(0x60FF, start_address);
+        chip8.load_opcode_into_memory(0x6101, start_address + 2);
+        chip8.load_opcode_into_memory(0x8014, start_address + 4);
+        chip8.run_instructions(3).expect("an error occurred during emulator execution");
 
         // verify result
-        assert_eq!(chip8.registers[0], (val_1 | val_2), "failed to correctly perform the bitwise OR operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        let expected = FlagChange { address: start_address + 4, opcode: 0x8014, old_value: 0, new_value: 1 };
+        assert_eq!(*flag_changes.borrow(), vec!(expected), "failed to report the carry flag");
     }
 
     #[test]
-    fn bitwise_and_x_y() {
-        let mut chip8 = init_emulator();
-
-        let val_1 = 64;
-        let val_2 = 15;
-
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+    fn memory_pattern() {
+        let mut chip8 = Chip8::new(Quirks::default());
+        chip8.set_memory_pattern(MemoryPattern::Ones);
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8012, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        let program_data = opcodes_to_bytes(&[0x6012, 0x0000]);
+        chip8.load_program(&program_data).expect("failed to load the program");
 
         // verify result
-        assert_eq!(chip8.registers[0], (val_1 & val_2), "failed to correctly perform the bitwise AND operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        let start_address = chip8.program_start_address as usize;
+        assert_eq!(chip8.memory[0x300], 0xFF, "failed to fill the untouched memory with the pattern");
+        assert_eq!(chip8.memory[0x000], 0xFF, "failed to fill the memory below the fonts with the pattern");
+        assert_eq!(&chip8.memory[start_address..(start_address + program_data.len())], program_data.as_slice(), "failed to keep the program intact");
+        assert_eq!(chip8.memory[FONT_START_ADDRESS as usize], DEFAULT_FONT_DATA[0], "failed to keep the font intact");
+
+        chip8.soft_reset();
+        assert_eq!(chip8.memory[0x300], 0xFF, "failed to apply the pattern on a soft reset");
     }
 
     #[test]
-    fn bitwise_xor_x_y() {
+    fn call_stack() {
         let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
 
-        let val_1 = 65;
-        let val_2 = 15;
-
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
-
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8013, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        // load opcodes (call a subroutine that calls another one)
+        chip8.load_opcode_into_memory(0x2000 | (start_address + 4), start_address);
+        chip8.load_opcode_into_memory(0x2000 | (start_address + 8), start_address + 4);
+        chip8.run_instructions(2).expect("an error occurred during emulator execution");
 
         // verify result
-        assert_eq!(chip8.registers[0], (val_1 ^ val_2), "failed to correctly perform the bitwise XOR operation on 2 registers; val_1: {}, val_2: {}, result: {}", val_1, val_2, chip8.registers[0]);
+        assert_eq!(chip8.call_stack(), &[start_address + 2, start_address + 6], "failed to return the return addresses in call order");
     }
 
     #[test]
-    fn right_bit_shift() {
-        let mut chip8 = init_emulator();
+    fn record_and_replay_input() {
+        // wait for a key and add it and a random number to V1 (a held key is read again and again)
+        let program_data = opcodes_to_bytes(&[0xF00A, 0x8104, 0xC2FF, 0x8124, 0x1200]);
+        let mut key_3 = Keypad::new();
+        key_3.set_key(0x3);
+        let mut key_5 = Keypad::new();
+        key_5.set_key(0x5);
 
-        let val_1 = 65;
+        let mut chip8 = init_emulator();
+        chip8.seed_random_generator(0xC8);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        chip8.start_recording();
+        for keypad in [key_3, Keypad::new(), key_5] {
+            chip8.tick(keypad, FRAME_DURATION).expect("an error occurred during emulator execution");
+        }
+        let recording = chip8.stop_recording();
 
-        // load registers
-        chip8.load_register(1, val_1);
+        assert_ne!(chip8.registers[1], 0, "failed to read the keys");
+        assert_eq!(recording.len(), 3, "failed to record every frame");
+        assert!(!chip8.is_recording(), "failed to stop recording");
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x8016, PROGRAM_START_ADDRESS);
-        run_emulator(&mut chip8);
+        let mut replay = init_emulator();
+        replay.seed_random_generator(0xC8);
+        replay.load_program(&program_data).expect("failed to load the program");
+        let outcome = replay.play_recording(&recording).expect("an error occurred during the replay");
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1 >> 1, "failed to correctly perform the right bit-shift operation; val_1: {}, result: {}", val_1, chip8.registers[0]);
-
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 1, "failed to correctly load the LSB into VF; VF register: 0x{:02x}", vf_register);
+        assert_eq!(outcome, RunOutcome::Continue, "failed to replay every frame");
+        assert_eq!(replay.registers, chip8.registers, "failed to reproduce the registers");
+        assert_eq!(replay.instructions_executed, chip8.instructions_executed, "failed to reproduce the executed instructions");
     }
 
     #[test]
-    fn left_bit_shift() {
+    #[allow(deprecated)]
+    fn load_register_out_of_range() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 255;
+        chip8.load_register(0xF, 0x12).expect("failed to load the register");
+        let result = chip8.load_register(16, 0x34);
 
-        // load registers
-        chip8.load_register(1, val_1);
+        // verify result
+        assert!(matches!(result, Err(Chip8Error::InvalidRegister(_))), "failed to reject the invalid register ID");
+        assert_eq!(chip8.registers[0xF], 0x12, "failed to load the valid register");
+    }
+
+    #[test]
+    fn set_index_to_char_big_font() {
+        let mut chip8 = init_emulator();
+        chip8.set_register(0, 5);
+        chip8.load_opcode_into_memory(0xF030, chip8.program_start_address);
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x801E, PROGRAM_START_ADDRESS);
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1 << 1, "failed to correctly perform the left bit-shift operation; val_1: {}, result: {}", val_1, chip8.registers[0]);
-
-        let vf_register = &chip8.registers[FLAG_REG_ID as usize];
-        assert_eq!(*vf_register, 1, "failed to correctly load the LSB into VF; VF register: 0x{:02x}", vf_register);
+        assert_eq!(chip8.index_reg, BIG_FONT_START_ADDRESS + (5 * 10), "failed to set the index register to the big font glyph; index_reg: 0x{:04x}", chip8.index_reg);
+        let glyph_address = chip8.index_reg as usize;
+        assert_eq!(&chip8.memory[glyph_address..(glyph_address + 10)], &DEFAULT_BIG_FONT_DATA[50..60], "failed to install the big font");
+        assert!(BIG_FONT_START_ADDRESS as usize + BIG_FONT_DATA_SIZE <= DEFAULT_PROGRAM_START_ADDRESS as usize, "the big font overlaps the program");
     }
 
     #[test]
-    fn skip_if_x_equals_const() {
+    fn load_big_font() {
         let mut chip8 = init_emulator();
+        let big_font_data = [0xAA; BIG_FONT_DATA_SIZE];
 
-        let val_1 = 5;
-
-        // load registers
-        chip8.load_register(0, val_1);
-
-        // load opcodes
-        let opcode: u16 = (0x3000 as u16) | (val_1 as u16);
-        chip8.load_opcode_into_memory(opcode, PROGRAM_START_ADDRESS);
-        // if the skip fails, V0 is set to 0x11
-        chip8.load_opcode_into_memory(0x6011, PROGRAM_START_ADDRESS + 2);
-        run_emulator(&mut chip8);
+        assert!(chip8.load_big_font(&big_font_data[..50]).is_err(), "failed to reject the incomplete big font");
+        chip8.load_big_font(&big_font_data).expect("failed to load the big font");
+        chip8.reset();
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX == NN) operation");
+        let big_font_region = (BIG_FONT_START_ADDRESS as usize)..(BIG_FONT_START_ADDRESS as usize + BIG_FONT_DATA_SIZE);
+        assert_eq!(&chip8.memory[big_font_region], &big_font_data, "failed to reinstall the big font on reset");
+        assert_eq!(&chip8.memory[(FONT_START_ADDRESS as usize)..(FONT_START_ADDRESS as usize + FONT_DATA_SIZE)], &DEFAULT_FONT_DATA, "falsely changed the font");
     }
 
     #[test]
-    fn skip_if_x_not_equals_const() {
+    fn dump_registers_to_memory() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 5;
+        let vals: [u8; 16] = [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
 
         // load registers
-        chip8.load_register(0, val_1);
+        chip8.load_registers(&vals);
+        chip8.index_reg = 0x300;
 
         // load opcodes
-        let opcode: u16 = (0x4000 as u16) | ((val_1 + 1) as u16);
-        chip8.load_opcode_into_memory(opcode, PROGRAM_START_ADDRESS);
-        // if the skip fails, V0 is set to 0x11
-        chip8.load_opcode_into_memory(0x6011, PROGRAM_START_ADDRESS + 2);
+        chip8.load_opcode_into_memory(0xFF55, chip8.program_start_address);
+
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX != NN) operation");
+        for (idx, val) in vals.iter().enumerate() {
+            assert_eq!(chip8.memory[(chip8.index_reg as usize) + idx], *val, "failed to correctly dump register V{:1X} into memory", idx);
+        }
     }
 
     #[test]
-    fn skip_if_x_equals_y() {
+    fn load_registers_from_memory() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 5;
+        let vals: Vec<u8> = vec!(16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1);
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_1);
+        chip8.index_reg = 0x300;
+
+        // load memory
+        chip8.load_bytes_into_memory(&vals, chip8.index_reg);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0x5010, PROGRAM_START_ADDRESS);
-        // if the skip fails, V0 is set to 0x11
-        chip8.load_opcode_into_memory(0x6011, PROGRAM_START_ADDRESS + 2);
+        chip8.load_opcode_into_memory(0xFF65, chip8.program_start_address);
+
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX == VY) operation");
+        for (idx, val) in vals.iter().enumerate() {
+            assert_eq!(chip8.registers[idx], *val, "failed to correctly load register V{:1X} from memory", idx);
+        }
     }
 
     #[test]
-    fn skip_if_x_not_equals_y() {
+    fn store_and_load_rpl_flags() {
         let mut chip8 = init_emulator();
 
-        let val_1 = 5;
+        let vals: [u8; 16] = [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_1 + 1);
+        chip8.load_registers(&vals);
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0x9010, PROGRAM_START_ADDRESS);
-        // if the skip fails, V0 is set to 0x11
-        chip8.load_opcode_into_memory(0x6011, PROGRAM_START_ADDRESS + 2);
+        // load opcodes (store all registers, clear them and load V0 - V2 again)
+        chip8.load_opcode_into_memory(0xFF75, chip8.program_start_address);
+        run_emulator(&mut chip8);
+        chip8.reset();
+        chip8.load_opcode_into_memory(0xF285, chip8.program_start_address);
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[0], val_1, "failed to correctly perform the if(VX != VY) operation");
+        assert_eq!(chip8.get_rpl_flags(), &vals[..RPL_FLAG_COUNT], "failed to store V0 - V7 in the RPL flags");
+        assert_eq!(&chip8.registers[..4], &[16, 15, 14, 0], "failed to correctly load V0 - V2 from the RPL flags");
     }
 
     #[test]
-    fn call_and_return_from_subroutine() {
+    fn dump_memory() {
+        let path = std::env::temp_dir().join("chip8_memory_dump.bin");
+
         let mut chip8 = init_emulator();
+        let program_data = opcodes_to_bytes(&[0x6005, 0x1200]);
+        chip8.load_program(&program_data).expect("failed to load the program");
+        chip8.dump_memory(&path).expect("failed to dump the memory");
 
-        let val_1 = 5;
-        let val_2 = 7;
+        let memory_dump = std::fs::read(&path).expect("failed to read the memory dump");
+        std::fs::remove_file(&path).expect("failed to remove the memory dump");
 
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        // verify result
+        assert_eq!(memory_dump.len(), DEFAULT_MEMORY_SIZE, "failed to dump the whole memory");
+        assert_eq!(memory_dump, chip8.get_memory(), "failed to dump the memory contents");
+        assert_eq!(&memory_dump[0x200..0x204], program_data.as_slice(), "failed to include the program");
+    }
 
-        // load opcodes
-        let main_opcodes: Vec<u16> = vec!(0x2300, 0x8014);
-        chip8.load_opcodes_into_memory(&main_opcodes, PROGRAM_START_ADDRESS);
+    #[test]
+    fn persist_rpl_flags() {
+        let path = std::env::temp_dir().join("chip8_rpl_flags.bin");
+        let _ = std::fs::remove_file(&path);
 
-        let subroutine_opcodes: Vec<u16> = vec!(0x8104, 0x00EE);
-        chip8.load_opcodes_into_memory(&subroutine_opcodes, 0x300);
+        let mut chip8 = init_emulator();
+        chip8.load_rpl_flags(&path).expect("failed to treat the missing file as all zero");
+        assert_eq!(chip8.get_rpl_flags(), &[0; RPL_FLAG_COUNT], "failed to treat the missing file as all zero");
 
-        run_emulator(&mut chip8);
+        chip8.rpl_flags = [1, 2, 3, 4, 5, 6, 7, 8];
+        chip8.save_rpl_flags(&path).expect("failed to save the RPL flags");
+
+        let mut other_chip8 = init_emulator();
+        other_chip8.load_rpl_flags(&path).expect("failed to load the RPL flags");
+        std::fs::remove_file(&path).expect("failed to remove the RPL flags file");
 
         // verify result
-        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly call subroutine");
+        assert_eq!(other_chip8.get_rpl_flags(), &[1, 2, 3, 4, 5, 6, 7, 8], "failed to restore the saved RPL flags");
+    }
 
-        assert_eq!(chip8.registers[0], val_1 * 2 + val_2, "failed to correctly return from subroutine");
+    #[test]
+    fn index_on_dump_or_load() {
+        // (mode, expected I after dumping/loading V0 to V3 from 0x300)
+        let cases = [
+            (IndexOnDumpLoad::PlusXPlus1, 0x304),
+            (IndexOnDumpLoad::PlusX, 0x303),
+            (IndexOnDumpLoad::Unchanged, 0x300),
+        ];
+
+        for (index_on_dump_or_load, expected_index) in cases {
+            for opcode in [0xF355, 0xF365] {
+                let mut chip8 = init_emulator();
+                chip8.set_quirks(Quirks { index_on_dump_or_load, ..Quirks::default() });
+                chip8.index_reg = 0x300;
+
+                // load opcodes
+                chip8.load_opcode_into_memory(opcode, chip8.program_start_address);
+                run_emulator(&mut chip8);
+
+                // verify result
+                assert_eq!(chip8.index_reg, expected_index, "failed to correctly modify I; mode: {:?}, opcode: 0x{:04x}, I: 0x{:04x}", index_on_dump_or_load, opcode, chip8.index_reg);
+            }
+        }
     }
 
     #[test]
-    fn jump_to_address() {
-        let mut chip8 = init_emulator();
+    fn write_to_xo_chip_memory() {
+        let mut chip8 = Chip8::with_memory_size(XO_CHIP_MEMORY_SIZE, Quirks::default());
 
-        let val_1 = 5;
-        let val_2 = 7;
+        let val_1 = 0x42;
 
         // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        chip8.set_register(0, val_1);
+        chip8.load_index_reg(0x2000);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0x2300, PROGRAM_START_ADDRESS);
-        chip8.load_opcode_into_memory(0x8104, 0x300);
-
+        chip8.load_opcode_into_memory(0xF055, chip8.program_start_address);
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly execute jump");
+        assert_eq!(chip8.memory.len(), XO_CHIP_MEMORY_SIZE, "failed to allocate the XO-CHIP memory space");
+        assert_eq!(chip8.memory[0x2000], val_1, "failed to correctly write beyond the 4KB boundary; mem[0x2000]: 0x{:02x}", chip8.memory[0x2000]);
     }
 
-    #[test]
-    fn jump_to_address_with_displacement() {
+    fn draw_sprite_twice(collision_mode: CollisionMode) -> u8 {
         let mut chip8 = init_emulator();
+        chip8.set_collision_mode(collision_mode);
 
-        let val_1 = 5;
-        let val_2 = 7;
-
-        // load registers
-        chip8.load_register(0, val_1);
-        chip8.load_register(1, val_2);
+        // 4 rows, one of them empty
+        let sprite: Vec<u8> = vec!(0xFF, 0x00, 0xFF, 0xFF);
+        chip8.load_bytes_into_memory(&sprite, 0x300);
+        chip8.load_index_reg(0x300);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0xB2FB, PROGRAM_START_ADDRESS);
-        chip8.load_opcode_into_memory(0x8104, 0x300);
-
+        let opcodes: Vec<u16> = vec!(0xD014, 0xD014);
+        chip8.load_opcodes_into_memory(&opcodes, chip8.program_start_address);
         run_emulator(&mut chip8);
 
-        // verify result
-        assert_eq!(chip8.registers[1], val_1 + val_2, "failed to correctly execute jump");
+        return chip8.registers[FLAG_REG_ID as usize];
     }
 
     #[test]
-    fn set_index_reg() {
-        let mut chip8 = init_emulator();
-
-        let val_1: u16 = 5;
-
-        // load opcodes
-        let opcode: u16 = (0xA000 as u16) | val_1;
-        chip8.load_opcode_into_memory(opcode, PROGRAM_START_ADDRESS);
-
-        run_emulator(&mut chip8);
+    fn display_sprite_binary_collision() {
+        let vf_register = draw_sprite_twice(CollisionMode::Binary);
+        assert_eq!(vf_register, 1, "failed to report the collision; VF register: 0x{:02x}", vf_register);
+    }
 
-        // verify result
-        assert_eq!(chip8.index_reg, val_1, "failed to correctly set the index register; index_reg: {}", chip8.index_reg);
+    #[test]
+    fn display_sprite_row_count_collision() {
+        let vf_register = draw_sprite_twice(CollisionMode::RowCount);
+        assert_eq!(vf_register, 3, "failed to count the collided rows; VF register: 0x{:02x}", vf_register);
     }
 
     #[test]
-    fn add_x_to_index() {
+    fn display_sprite_or_draw_mode() {
         let mut chip8 = init_emulator();
+        chip8.set_sprite_draw_mode(SpriteDrawMode::Or);
 
-        let val_1: u16 = 5;
-        let val_2: u8 = 7;
-
-        // load registers
-        chip8.load_index_reg(val_1);
-        chip8.load_register(0, val_2);
+        chip8.load_bytes_into_memory(&[0xFF, 0x81], 0x300);
+        chip8.load_index_reg(0x300);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0xF01E, PROGRAM_START_ADDRESS);
-
+        let opcodes: Vec<u16> = vec!(0xD012, 0xD012);
+        chip8.load_opcodes_into_memory(&opcodes, chip8.program_start_address);
         run_emulator(&mut chip8);
 
         // verify result
-        assert_eq!(chip8.index_reg, val_1 + (val_2 as u16), "failed to correctly add to X to index register; index_reg: {}", chip8.index_reg);
+        let vf_register = chip8.registers[FLAG_REG_ID as usize];
+        assert_eq!(vf_register, 0, "failed to never report a collision; VF register: 0x{:02x}", vf_register);
+        assert!((0..8).all(|x_pos| chip8.frame().get_pixel(x_pos, 0)), "failed to keep the pixels of the first row");
+        assert!(chip8.frame().get_pixel(0, 1) && chip8.frame().get_pixel(7, 1), "failed to keep the pixels of the second row");
+        assert!(!chip8.frame().get_pixel(1, 1), "failed to keep the pixels outside of the sprite off");
     }
 
-    #[test]
-    fn set_index_to_char_font() {
+    /// draws a full 8x2 sprite at the given position and returns the frame
+    fn draw_sprite_at(sprite_wrap: SpriteWrap, x_pos: u8, y_pos: u8) -> FrameBuffer {
         let mut chip8 = init_emulator();
+        chip8.set_quirks(Quirks { sprite_wrap, ..Quirks::default() });
 
-        let val_1: u8 = 0xF;
-
-        // load registers
-        chip8.load_register(0, val_1);
+        chip8.load_bytes_into_memory(&[0xFF, 0xFF], 0x300);
+        chip8.load_index_reg(0x300);
+        chip8.set_register(0, x_pos);
+        chip8.set_register(1, y_pos);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0xF029, PROGRAM_START_ADDRESS);
-
+        chip8.load_opcode_into_memory(0xD012, chip8.program_start_address);
         run_emulator(&mut chip8);
 
-        // verify result
-        assert_eq!(chip8.index_reg, FONT_START_ADDRESS + (15 * 5), "failed to correctly set the index register to the fonts location; index_reg: 0x{:04x}; character: 0x{:02x}", chip8.index_reg, val_1);
+        return chip8.frame().clone();
     }
 
     #[test]
-    fn dump_registers_to_memory() {
-        let mut chip8 = init_emulator();
-
-        let vals: [u8; 16] = [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
+    fn display_sprite_wrap_origin() {
+        // 68 % 64 = 4; 33 % 32 = 1
+        let frame = draw_sprite_at(SpriteWrap::OriginOnly, 68, 33);
+        assert!(frame.get_pixel(4, 1) && frame.get_pixel(11, 2), "failed to correctly wrap the starting coordinate");
+
+        // the overflow is clipped
+        let frame = draw_sprite_at(SpriteWrap::OriginOnly, 60, 31);
+        assert!(frame.get_pixel(63, 31), "failed to correctly draw the visible part of the sprite");
+        assert!(!frame.get_pixel(0, 31) && !frame.get_pixel(60, 0), "failed to correctly clip the overflow");
+    }
 
-        // load registers
-        chip8.load_registers(&vals);
-        chip8.index_reg = 0x300;
+    #[test]
+    fn display_sprite_wrap_never() {
+        let frame = draw_sprite_at(SpriteWrap::Never, 68, 33);
+        assert!(frame.pixels().iter().all(|&pixel| !pixel), "failed to correctly skip the sprite outside the screen");
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0xFF55, PROGRAM_START_ADDRESS);
+        let frame = draw_sprite_at(SpriteWrap::Never, 60, 31);
+        assert!(frame.get_pixel(63, 31), "failed to correctly draw the visible part of the sprite");
+        assert!(!frame.get_pixel(0, 31) && !frame.get_pixel(60, 0), "failed to correctly clip the overflow");
+    }
 
-        run_emulator(&mut chip8);
+    #[test]
+    fn display_sprite_wrap_always() {
+        let frame = draw_sprite_at(SpriteWrap::Always, 60, 31);
 
         // verify result
-        for (idx, val) in vals.iter().enumerate() {
-            assert_eq!(chip8.memory[(chip8.index_reg as usize) + idx], *val, "failed to correctly dump register V{:1X} into memory", idx);
-        }
+        assert!(frame.get_pixel(63, 31), "failed to correctly draw the visible part of the sprite");
+        assert!(frame.get_pixel(3, 31) && frame.get_pixel(60, 0) && frame.get_pixel(3, 0), "failed to correctly wrap the overflow");
     }
 
     #[test]
-    fn load_registers_from_memory() {
-        let mut chip8 = init_emulator();
-
-        let vals: Vec<u8> = vec!(16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1);
+    fn scroll_down_lores_half() {
+        let mut chip8 = Chip8::new(Quirks::SUPER_CHIP);
+        let start_address = chip8.program_start_address;
 
-        // load registers
-        chip8.index_reg = 0x300;
+        // load opcodes (draw the font sprite of "0" at (0, 0), then scroll down by 4 hires rows)
+        chip8.load_opcode_into_memory(0xA000 | FONT_START_ADDRESS, start_address);
+        chip8.load_opcode_into_memory(0xD005, start_address + 2);
+        chip8.load_opcode_into_memory(0x00C4, start_address + 4);
+        chip8.run_instructions(3).expect("an error occurred during emulator execution");
 
-        // load memory
-        chip8.load_bytes_into_memory(&vals, chip8.index_reg);
+        // verify result
+        assert!(!chip8.frame().get_pixel(0, 1) && chip8.frame().get_pixel(0, 2), "failed to scroll by half the rows in lores mode");
+    }
 
-        // load opcodes
-        chip8.load_opcode_into_memory(0xFF65, PROGRAM_START_ADDRESS);
+    #[test]
+    fn display_sprite_out_of_bounds() {
+        let mut chip8 = init_emulator();
+        let start_address = chip8.program_start_address;
+        let memory_size = chip8.memory.len() as u16;
 
-        run_emulator(&mut chip8);
+        // load opcodes (point the index register to the last 4 bytes of memory, then draw 5 rows)
+        chip8.load_opcode_into_memory(0xA000 | (memory_size - 4), start_address);
+        chip8.load_opcode_into_memory(0xD005, start_address + 2);
+        chip8.run_instructions(1).expect("an error occurred during emulator execution");
 
         // verify result
-        for (idx, val) in vals.iter().enumerate() {
-            assert_eq!(chip8.registers[idx], *val, "failed to correctly load register V{:1X} from memory", idx);
-        }
+        let result = chip8.exec_next_instruction();
+        assert!(matches!(result, Err(Chip8Error::MemoryOutOfBounds(_))), "failed to report the sprite read past the end of memory");
+        assert!(chip8.read_sprite_bytes(4).is_ok(), "falsely rejected the sprite data at the end of memory");
     }
 
     #[test]
@@ -1135,10 +4142,10 @@ mod tests {
         let val_1 = 123;
 
         // load registers
-        chip8.load_register(0, val_1);
+        chip8.set_register(0, val_1);
 
         // load opcodes
-        chip8.load_opcode_into_memory(0xF033, PROGRAM_START_ADDRESS);
+        chip8.load_opcode_into_memory(0xF033, chip8.program_start_address);
         run_emulator(&mut chip8);
 
         // verify result
@@ -1146,4 +4153,17 @@ mod tests {
         assert_eq!(chip8.memory[(chip8.index_reg as usize) + 1], 2, "failed to correctly extract the decimal tens; value: {}, tens: {}", val_1, chip8.memory[(chip8.index_reg as usize) + 1]);
         assert_eq!(chip8.memory[(chip8.index_reg as usize) + 2], 3, "failed to correctly extract the decimal ones; value: {}, ones: {}", val_1, chip8.memory[(chip8.index_reg as usize) + 2]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn dump_state_text() {
+        let mut chip8 = init_emulator();
+        chip8.load_opcodes_into_memory(&vec!(0x2206, 0x0000, 0x0000, 0x6A2A, 0xA123, 0x0000), DEFAULT_PROGRAM_START_ADDRESS);
+        run_emulator(&mut chip8);
+
+        let text = chip8.dump_state_text();
+        for expected in ["REGISTERS:", "\tVA: 0x2a =  42", "I: 0x0123", "SP: 1 (depth 1/24)", "STACK:\n\t0x0202\n", "DELAY TIMER: 0", "QUIRKS: Quirks {", "SCREEN (64x32):"] {
+            assert!(text.contains(expected), "failed to include '{}' in the state dump", expected);
+        }
+        assert!(text.ends_with(&chip8.frame().to_ascii()), "failed to include the screen");
+    }
+}
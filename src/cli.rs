@@ -0,0 +1,269 @@
+use crate::config::ApplicationConfig;
+use crate::quirks::Quirks;
+
+/// number of frames that are run in headless mode if `--frames` isn't specified (10 seconds at 60 FPS)
+const DEFAULT_HEADLESS_FRAMES: u64 = 600;
+
+/// path that makes `--dump-screen` write to stdout instead of a file
+pub const STDOUT_PATH: &str = "-";
+
+/// path that makes `--rom` read the program from stdin instead of a file
+pub const STDIN_PATH: &str = "-";
+
+/// environment variable that has the same effect as `--no-prompt` if it's set (e.g. in CI)
+pub const NONINTERACTIVE_ENV_VAR: &str = "CHIP8_NONINTERACTIVE";
+
+#[derive(Debug, PartialEq)]
+pub struct CliArgs {
+    /// overrides the `program_path` attribute of the config (`-` for stdin)
+    pub rom_path: Option<String>,
+
+    /// overrides the `screen_scale` attribute of the config
+    pub screen_scale: Option<u32>,
+
+    /// overrides the `instructions_per_second` attribute of the config
+    pub instructions_per_second: Option<u32>,
+
+    /// overrides the `profile` attribute of the config (and thus the quirks)
+    pub profile: Option<String>,
+
+    /// overrides the pixel color (first plane) of the `palette` attribute of the config
+    pub foreground_color: Option<[u8; 3]>,
+
+    /// overrides the background color of the `palette` attribute of the config
+    pub background_color: Option<[u8; 3]>,
+
+    /// run without a window (and without audio or input)
+    pub headless: bool,
+
+    /// number of frames to run in headless mode
+    pub frames: u64,
+
+    /// file to write the final frame buffer to as ASCII art (`-` for stdout)
+    pub dump_screen_path: Option<String>,
+
+    /// never ask for user input (the config file is used if it exists, otherwise the default config is written)
+    ///  - also set if the [`NONINTERACTIVE_ENV_VAR`] environment variable is set
+    pub no_prompt: bool,
+
+    /// run the built-in diagnostic program instead of a ROM (see [`crate::selftest`])
+    pub selftest: bool,
+
+    /// print the quirks of the configuration (see [`crate::quirk_test`]) and exit
+    pub quirk_report: bool,
+
+    /// log every write of the flag register VF (see [`crate::chip8::Chip8::set_flag_hook()`])
+    pub log_flags: bool,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        return CliArgs {
+            rom_path: None,
+            screen_scale: None,
+            instructions_per_second: None,
+            profile: None,
+            foreground_color: None,
+            background_color: None,
+            headless: false,
+            frames: DEFAULT_HEADLESS_FRAMES,
+            dump_screen_path: None,
+            no_prompt: false,
+            selftest: false,
+            quirk_report: false,
+            log_flags: false,
+        };
+    }
+}
+
+impl CliArgs {
+    /// replaces the attributes of the config that are given on the command line (so they take precedence over the
+    /// config file)
+    pub fn apply_to_config(&self, config: &mut ApplicationConfig) {
+        if let Some(rom_path) = &self.rom_path {
+            config.program_path = rom_path.clone();
+        }
+
+        if let Some(screen_scale) = self.screen_scale {
+            config.screen_scale = screen_scale;
+        }
+
+        if let Some(instructions_per_second) = self.instructions_per_second {
+            config.instructions_per_second = Some(instructions_per_second);
+        }
+
+        // the profile was validated while parsing
+        if let Some(profile) = &self.profile {
+            if let Some(quirks) = Quirks::from_profile(profile) {
+                config.profile = profile.clone();
+                config.quirks = quirks;
+            }
+        }
+
+        if let Some(foreground_color) = self.foreground_color {
+            config.palette[1] = foreground_color;
+        }
+
+        if let Some(background_color) = self.background_color {
+            config.palette[0] = background_color;
+        }
+    }
+}
+
+/// parses the command line arguments (without the program name)
+///
+/// usage: `chip-8-emulator [<path|->] [--rom <path|->] [--scale <n>] [--ips <n>] [--profile <name>] [--fg <RRGGBB>]
+/// [--bg <RRGGBB>] [--headless] [--frames <n>] [--dump-screen <path|->] [--no-prompt] [--selftest] [--quirk-report]
+/// [--log-flags]`
+pub fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<CliArgs, String> {
+    let mut cli_args = CliArgs::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rom" => cli_args.rom_path = Some(get_value(&mut args, &arg)?),
+            "--scale" => {
+                let value = get_value(&mut args, &arg)?;
+                cli_args.screen_scale = Some(value.parse::<u32>().ok().filter(|scale| *scale > 0).ok_or(format!("invalid screen scale '{}'!", value))?);
+            },
+            "--ips" => {
+                let value = get_value(&mut args, &arg)?;
+                cli_args.instructions_per_second = Some(value.parse::<u32>().ok().filter(|ips| *ips > 0).ok_or(format!("invalid number of instructions per second '{}'!", value))?);
+            },
+            "--profile" => {
+                let value = get_value(&mut args, &arg)?;
+                if Quirks::from_profile(&value).is_none() {
+                    return Err(format!("there is no quirk profile called '{}'!", value));
+                }
+                cli_args.profile = Some(value);
+            },
+            "--fg" => cli_args.foreground_color = Some(parse_color(&get_value(&mut args, &arg)?)?),
+            "--bg" => cli_args.background_color = Some(parse_color(&get_value(&mut args, &arg)?)?),
+            "--headless" => cli_args.headless = true,
+            "--frames" => {
+                let value = get_value(&mut args, &arg)?;
+                cli_args.frames = value.parse::<u64>().map_err(|_| format!("invalid number of frames '{}'!", value))?;
+            },
+            "--dump-screen" => cli_args.dump_screen_path = Some(get_value(&mut args, &arg)?),
+            "--no-prompt" => cli_args.no_prompt = true,
+            "--selftest" => cli_args.selftest = true,
+            "--quirk-report" => cli_args.quirk_report = true,
+            "--log-flags" => cli_args.log_flags = true,
+            // the ROM path can also be given without `--rom` (e.g. `chip-8-emulator pong.ch8`)
+            _ if !arg.starts_with("--") && cli_args.rom_path.is_none() => cli_args.rom_path = Some(arg),
+            _ => return Err(format!("unknown argument '{}'!", arg)),
+        }
+    }
+
+    return Ok(cli_args);
+}
+
+/// parses a color in hexadecimal notation (`RRGGBB`, optionally with a leading `#`)
+fn parse_color(value: &str) -> Result<[u8; 3], String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        return Err(format!("invalid color '{}' (expected RRGGBB)!", value));
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&hex[(idx * 2)..(idx * 2 + 2)], 16).unwrap();
+    return Ok([channel(0), channel(1), channel(2)]);
+}
+
+fn get_value<I: Iterator<Item=String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    return args.next().ok_or(format!("missing value for '{}'!", flag));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> impl Iterator<Item=String> {
+        return args.iter().map(|arg| String::from(*arg)).collect::<Vec<String>>().into_iter();
+    }
+
+    #[test]
+    fn parse_headless_args() {
+        let cli_args = parse_args(to_args(&["--rom", "x.ch8", "--headless", "--frames", "600", "--dump-screen", "out.txt"])).expect("failed to parse the arguments");
+
+        assert_eq!(cli_args, CliArgs {
+            rom_path: Some(String::from("x.ch8")),
+            screen_scale: None,
+            instructions_per_second: None,
+            profile: None,
+            foreground_color: None,
+            background_color: None,
+            headless: true,
+            frames: 600,
+            dump_screen_path: Some(String::from("out.txt")),
+            no_prompt: false,
+            selftest: false,
+            quirk_report: false,
+            log_flags: false,
+        });
+    }
+
+    #[test]
+    fn parse_no_prompt_args() {
+        let cli_args = parse_args(to_args(&["--no-prompt"])).expect("failed to parse the arguments");
+
+        assert!(cli_args.no_prompt, "failed to parse the no-prompt flag");
+        assert!(!cli_args.headless, "failed to keep the window mode");
+
+        let cli_args = parse_args(to_args(&["--quirk-report"])).expect("failed to parse the arguments");
+        assert!(cli_args.quirk_report, "failed to parse the quirk report flag");
+
+        let cli_args = parse_args(to_args(&["--log-flags"])).expect("failed to parse the arguments");
+        assert!(cli_args.log_flags, "failed to parse the flag logging flag");
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        assert!(parse_args(to_args(&["--frames", "many"])).is_err(), "failed to reject the invalid number of frames");
+        assert!(parse_args(to_args(&["--rom"])).is_err(), "failed to reject the missing value");
+        assert!(parse_args(to_args(&["--fullscreen"])).is_err(), "failed to reject the unknown argument");
+        assert!(parse_args(to_args(&["--scale", "0"])).is_err(), "failed to reject the zero screen scale");
+        assert!(parse_args(to_args(&["--profile", "chip9"])).is_err(), "failed to reject the unknown profile");
+        assert!(parse_args(to_args(&["--fg", "#12345"])).is_err(), "failed to reject the short color");
+        assert!(parse_args(to_args(&["a.ch8", "b.ch8"])).is_err(), "failed to reject the second ROM path");
+    }
+
+    #[test]
+    fn apply_args_to_config() {
+        // (arguments, expected config)
+        let default_config = ApplicationConfig::default();
+        let cases = [
+            (vec!(), default_config.clone()),
+            (vec!("pong.ch8", "--scale", "10", "--ips", "1000"), ApplicationConfig {
+                program_path: String::from("pong.ch8"),
+                screen_scale: 10,
+                instructions_per_second: Some(1000),
+                ..default_config.clone()
+            }),
+            (vec!("--profile", "schip", "--fg", "#FF8000", "--bg", "102030"), ApplicationConfig {
+                profile: String::from("schip"),
+                quirks: Quirks::SUPER_CHIP,
+                palette: [[0x10, 0x20, 0x30], [0xFF, 0x80, 0x00], default_config.palette[2], default_config.palette[3]],
+                ..default_config.clone()
+            }),
+        ];
+
+        for (args, expected_config) in cases {
+            let cli_args = parse_args(to_args(&args)).expect("failed to parse the arguments");
+            let mut config = ApplicationConfig::default();
+            cli_args.apply_to_config(&mut config);
+
+            assert_eq!(config, expected_config, "failed to apply the arguments {:?}", args);
+        }
+    }
+
+    #[test]
+    fn profile_arg_takes_precedence_over_known_rom() {
+        let rom = include_bytes!("../programs/scroll.ch8");
+        assert_eq!(crate::quirks::detect_profile(rom), Some(Quirks::SUPER_CHIP), "failed to detect the scroll program");
+
+        let cli_args = parse_args(to_args(&["scroll.ch8", "--profile", "chip8"])).expect("failed to parse the arguments");
+        let mut config = ApplicationConfig::default();
+        cli_args.apply_to_config(&mut config);
+
+        assert_eq!(config.quirks_for_rom(Some("scroll.ch8"), rom), Quirks::COSMAC_VIP, "failed to prefer the profile over the detected quirks");
+    }
+}
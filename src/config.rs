@@ -1,35 +1,177 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::io;
 use std::io::{Error};
 use std::str::FromStr;
 use confy::ConfyError;
 use serde::{Serialize, Deserialize};
+use crate::quirks::{self, IndexOnDumpLoad, QuirkOverrides, Quirks, UnknownOpcodePolicy};
+use crate::screen::SpriteWrap;
+use crate::chip8;
+use crate::stack;
 
 const CONFIG_PATH: &str = "./config/chip8-emulator.toml";
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug)]
+pub enum ConfigError {
+    Confy(ConfyError),
+    UnknownProfile(String),
+}
+
+impl From<ConfyError> for ConfigError {
+    fn from(err: ConfyError) -> Self {
+        return ConfigError::Confy(err);
+    }
+}
+
+/// **NOTE:** missing attributes fall back to their default values, so older config files can still be loaded
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
 pub struct ApplicationConfig {
     pub screen_scale: u32,
+
+    /// scale the screen by the largest integer factor that fits the window (centered, with margins in the background
+    /// color), so the pixels stay square; otherwise it's stretched to fill the window
+    pub integer_scale: bool,
+
     pub font_path: String,
+
+    /// file with the big font of SUPER-CHIP (10 bytes per digit; empty = the built-in big font)
+    pub big_font_path: String,
+
     pub program_path: String,
+
+    /// print the final machine state (see `Chip8::dump_state_text()`) once the program halts
+    pub print_state_on_halt: bool,
+
+    /// open a stereo audio device instead of a mono one
+    pub stereo_audio: bool,
+
+    /// panning of the sound in stereo mode (`-1.0` = left; `0.0` = center; `1.0` = right)
+    pub audio_balance: f32,
+
+    /// never play any sound (can be toggled with `M`)
+    pub audio_muted: bool,
+
+    /// duration of the fade in and out of the beep in milliseconds (avoids clicks; `0.0` = switch abruptly)
+    pub audio_ramp_ms: f32,
+
+    /// draw dark pixels on a light background (can be toggled with `I`)
+    pub invert_colors: bool,
+
+    /// pause the emulator while the window isn't focused (the sound is always paused)
+    pub pause_on_focus_loss: bool,
+
+    /// draw lines between the pixels (can be toggled with `G`; only visible from a screen scale of 3)
+    pub show_grid: bool,
+
+    /// color of the grid lines (RGB)
+    pub grid_color: [u8; 3],
+
+    /// colors of the pixels (RGB): background, first plane, second plane and both planes (XO-CHIP)
+    ///  - programs that only use one plane are drawn with the first 2 colors
+    pub palette: [[u8; 3]; 4],
+
+    /// name of the quirk preset to run programs with (see [`Quirks::PROFILES`]: "chip8", "schip", "xochip" or "octo")
+    ///  - replaces `quirks` when the config is loaded
+    ///  - an empty name keeps the individually set `quirks`
+    pub profile: String,
+
+    /// the quirks to run programs with (unless the program is known to need others)
+    pub quirks: Quirks,
+
+    /// quirks of single programs, keyed by file name (e.g. `pong.ch8`), which replace the ones of `quirks`
+    pub rom_quirks: HashMap<String, QuirkOverrides>,
+
+    /// number of return addresses the stack holds (e.g. 12 for the COSMAC VIP or 16 for SUPER-CHIP)
+    pub stack_depth: usize,
+
+    /// keep the RPL flags of SUPER-CHIP programs (often used for high scores) across sessions
+    pub persist_rpl_flags: bool,
+
+    /// file the RPL flags are persisted in
+    pub rpl_flags_path: String,
+
+    /// log a warning whenever the program writes into its own code (usually a bug, but some programs do it on purpose)
+    pub warn_on_code_writes: bool,
+
+    /// value of the memory outside of the fonts and the program (e.g. for reproducing reads of uninitialized memory)
+    pub memory_pattern: chip8::MemoryPattern,
+
+    /// speed of the emulator (`None` = the default clock speed of roughly 700 instructions per second)
+    pub instructions_per_second: Option<u32>,
+
+    /// maximum number of instructions per frame (the emulator skips ahead instead of catching up after a stall)
+    pub max_instructions_per_frame: u64,
+
+    /// time each instruction takes per opcode group (index = highest nibble of the opcode), in multiples of the regular
+    /// instruction duration (e.g. `[3, 1, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 8, 1, 3]` approximates the COSMAC VIP)
+    pub instruction_costs: [u32; 16],
 }
 
 impl Default for ApplicationConfig {
     fn default() -> Self {
         return Self {
             screen_scale: 20,
+            integer_scale: true,
             font_path: String::from("./fonts/chip48.font"),
+            big_font_path: String::new(),
             program_path: String::from("./programs/welcome.ch8"),
+            print_state_on_halt: false,
+            stereo_audio: false,
+            audio_balance: 0.0,
+            audio_muted: false,
+            audio_ramp_ms: 2.0,
+            invert_colors: false,
+            pause_on_focus_loss: false,
+            show_grid: false,
+            grid_color: [48, 48, 48],
+            palette: [[0, 0, 0], [255, 255, 255], [170, 170, 170], [85, 85, 85]],
+            profile: String::new(),
+            quirks: Quirks::default(),
+            rom_quirks: HashMap::new(),
+            stack_depth: stack::DEFAULT_DEPTH,
+            persist_rpl_flags: false,
+            rpl_flags_path: String::from("./config/rpl_flags.bin"),
+            warn_on_code_writes: false,
+            memory_pattern: chip8::MemoryPattern::default(),
+            instructions_per_second: None,
+            max_instructions_per_frame: chip8::DEFAULT_MAX_INSTRUCTIONS_PER_FRAME,
+            instruction_costs: chip8::UNIFORM_INSTRUCTION_COSTS,
         };
     }
 }
 
-pub fn load_config() -> Result<ApplicationConfig, ConfyError> {
+impl ApplicationConfig {
+    /// returns the quirks to run a program with
+    ///  1. `quirks`, or the recommended quirks if the program is known (see [`quirks::detect_profile()`]) and the user
+    ///     didn't choose any quirks (neither a `profile` nor `quirks` that differ from the defaults, e.g. set by the config
+    ///     dialog, the config file or the command line)
+    ///  2. overridden by the entry of `rom_quirks` that matches the file name of `rom_path` (if any)
+    pub fn quirks_for_rom(&self, rom_path: Option<&str>, rom_data: &[u8]) -> Quirks {
+        let quirks = if self.profile.is_empty() && self.quirks == Quirks::default() {
+            quirks::detect_profile(rom_data).unwrap_or(self.quirks)
+        } else {
+            self.quirks
+        };
+
+        let file_name = rom_path
+            .and_then(|rom_path| Path::new(rom_path).file_name())
+            .and_then(|file_name| file_name.to_str());
+
+        return match file_name.and_then(|file_name| self.rom_quirks.get(file_name)) {
+            Some(overrides) => overrides.apply_to(quirks),
+            None => quirks,
+        };
+    }
+}
+
+pub fn load_config() -> Result<ApplicationConfig, ConfigError> {
     let path = Path::new(CONFIG_PATH);
     return if path.exists() && {
         get_decision_input("continue with config?")
     } {
-        Ok(confy::load_path(path)?)
+        apply_profile(confy::load_path(path)?)
     } else {
         let config: ApplicationConfig = run_application_config_dialog();
 
@@ -40,6 +182,45 @@ pub fn load_config() -> Result<ApplicationConfig, ConfyError> {
     };
 }
 
+/// loads the config file if it exists and writes the default config otherwise, without any user interaction
+pub fn load_config_or_default() -> Result<ApplicationConfig, ConfigError> {
+    return load_config_or_default_from(Path::new(CONFIG_PATH));
+}
+
+fn load_config_or_default_from(path: &Path) -> Result<ApplicationConfig, ConfigError> {
+    return if path.exists() {
+        apply_profile(confy::load_path(path)?)
+    } else {
+        let config = ApplicationConfig::default();
+
+        // store config
+        confy::store_path(path, config.clone())?;
+
+        Ok(config)
+    };
+}
+
+/// changes settings in the config file (based on the stored config, so temporary overrides like command line arguments
+/// are not persisted)
+pub fn update_config_file<F: FnOnce(&mut ApplicationConfig)>(update: F) -> Result<(), ConfigError> {
+    let mut config = load_config_or_default()?;
+    update(&mut config);
+
+    confy::store_path(Path::new(CONFIG_PATH), config)?;
+
+    return Ok(());
+}
+
+/// replaces the quirks with the preset named by `profile` (if it's set)
+fn apply_profile(mut config: ApplicationConfig) -> Result<ApplicationConfig, ConfigError> {
+    if !config.profile.is_empty() {
+        config.quirks = Quirks::from_profile(&config.profile)
+            .ok_or_else(|| ConfigError::UnknownProfile(format!("there is no quirk profile called \"{}\"!", config.profile)))?;
+    }
+
+    return Ok(config);
+}
+
 pub fn run_application_config_dialog() -> ApplicationConfig {
     println!("==== CHIP-8 EMULATOR CONFIG ====");
 
@@ -52,10 +233,56 @@ pub fn run_application_config_dialog() -> ApplicationConfig {
     // get user input for program_path
     let program_path: String = get_path_input("program_path");
 
+    // get user input for quirks
+    let quirks: Quirks = get_quirks_input();
+
     return ApplicationConfig {
         screen_scale,
         font_path,
         program_path,
+        quirks,
+        ..ApplicationConfig::default()
+    };
+}
+
+/// lets the user pick a preset by interpreter name or set each quirk individually (defaults to the COSMAC VIP)
+fn get_quirks_input() -> Quirks {
+    let mut options: Vec<&str> = Quirks::PRESETS.iter().map(|(name, _)| *name).collect();
+    options.push("advanced");
+
+    let choice = get_choice_input("quirks", &options, 0);
+    if choice < Quirks::PRESETS.len() {
+        return Quirks::PRESETS[choice].1;
+    }
+
+    let sprite_wrap_options = ["wrap the origin only", "always wrap", "never wrap"];
+    let sprite_wrap = match get_choice_input("sprite_wrap", &sprite_wrap_options, 0) {
+        0 => SpriteWrap::OriginOnly,
+        1 => SpriteWrap::Always,
+        _ => SpriteWrap::Never,
+    };
+
+    let index_options = ["I = I + X + 1", "I = I + X", "I unchanged"];
+    let index_on_dump_or_load = match get_choice_input("index_on_dump_or_load", &index_options, 0) {
+        0 => IndexOnDumpLoad::PlusXPlus1,
+        1 => IndexOnDumpLoad::PlusX,
+        _ => IndexOnDumpLoad::Unchanged,
+    };
+
+    let unknown_opcode_options = ["stop with an error", "ignore the instruction", "halt"];
+    let unknown_opcode_policy = match get_choice_input("unknown_opcode_policy", &unknown_opcode_options, 0) {
+        0 => UnknownOpcodePolicy::Error,
+        1 => UnknownOpcodePolicy::Ignore,
+        _ => UnknownOpcodePolicy::Halt,
+    };
+
+    return Quirks {
+        assign_before_shift: get_decision_input("assign VY to VX before shifting?"),
+        set_flag_on_index_overflow: get_decision_input("set VF when I overflows?"),
+        index_on_dump_or_load,
+        sprite_wrap,
+        unknown_opcode_policy,
+        lores_scroll_half: get_decision_input("scroll by half the rows in lores mode?"),
     };
 }
 
@@ -97,6 +324,32 @@ fn get_path_input(value_description: &str) -> String {
     }
 }
 
+/// returns the index of the chosen option (an empty input selects the default option)
+fn get_choice_input(value_description: &str, options: &[&str], default_idx: usize) -> usize {
+    loop {
+        println!("{}: ", value_description);
+        for (idx, option) in options.iter().enumerate() {
+            let default_marker = if idx == default_idx { " (default)" } else { "" };
+            println!("\t{}: {}{}", idx + 1, option, default_marker);
+        }
+
+        if let Ok(input) = get_user_input() {
+            let input = input.trim();
+            if input.is_empty() {
+                return default_idx;
+            }
+
+            if let Ok(number) = input.parse::<usize>() {
+                if number >= 1 && number <= options.len() {
+                    return number - 1;
+                }
+            }
+
+            println!("invalid input!");
+        }
+    }
+}
+
 fn get_parsed_input<T: FromStr>(value_description: &str) -> T {
     loop {
         println!("{}: ", value_description);
@@ -113,4 +366,71 @@ fn get_parsed_input<T: FromStr>(value_description: &str) -> T {
             println!("invalid input!");
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn apply_profile_from_config() {
+        let config: ApplicationConfig = toml::from_str("profile = \"schip\"").expect("failed to deserialize the config");
+        let config = apply_profile(config).expect("failed to apply the profile");
+
+        assert_eq!(config.quirks, Quirks::SUPER_CHIP, "failed to replace the quirks with the profile");
+    }
+
+    #[test]
+    fn rom_quirks_override_global_quirks() {
+        let config: ApplicationConfig = toml::from_str("
+            profile = \"chip8\"
+
+            [rom_quirks.\"pong.ch8\"]
+            assign_before_shift = false
+        ").expect("failed to deserialize the config");
+        let config = apply_profile(config).expect("failed to apply the profile");
+
+        let expected_quirks = Quirks { assign_before_shift: false, ..Quirks::COSMAC_VIP };
+        assert_eq!(config.quirks_for_rom(Some("./programs/pong.ch8"), &[0x12, 0x00]), expected_quirks, "failed to merge the overrides of the program");
+        assert_eq!(config.quirks_for_rom(Some("./programs/tetris.ch8"), &[0x12, 0x00]), Quirks::COSMAC_VIP, "failed to keep the global quirks for other programs");
+        assert_eq!(config.quirks_for_rom(None, &[0x12, 0x00]), Quirks::COSMAC_VIP, "failed to keep the global quirks without a file name");
+    }
+
+    #[test]
+    fn detect_quirks_of_known_rom() {
+        let rom = include_bytes!("../programs/scroll.ch8");
+
+        let config = ApplicationConfig::default();
+        assert_eq!(config.quirks_for_rom(Some("scroll.ch8"), rom), Quirks::SUPER_CHIP, "failed to use the detected quirks");
+
+        // like stored by the config dialog, which sets the quirks of the chosen preset (but no profile)
+        let config = ApplicationConfig { quirks: Quirks::COSMAC_VIP, ..ApplicationConfig::default() };
+        assert_eq!(config.quirks_for_rom(Some("scroll.ch8"), rom), Quirks::COSMAC_VIP, "failed to keep the quirks chosen by the user");
+    }
+
+    #[test]
+    fn load_config_without_prompt() {
+        let path = std::env::temp_dir().join(format!("chip8-emulator-config-test-{}.toml", std::process::id()));
+
+        // an existing config file is loaded as is
+        fs::write(&path, "screen_scale = 7").expect("failed to write the config file");
+        let config = load_config_or_default_from(&path).expect("failed to load the config file");
+        assert_eq!(config.screen_scale, 7, "failed to load the existing config file");
+
+        // without a config file, the default config is used (and written)
+        fs::remove_file(&path).expect("failed to remove the config file");
+        let config = load_config_or_default_from(&path).expect("failed to fall back to the default config");
+        assert_eq!(config, ApplicationConfig::default(), "failed to fall back to the default config");
+        assert!(path.exists(), "failed to write the default config file");
+
+        fs::remove_file(&path).expect("failed to remove the config file");
+    }
+
+    #[test]
+    fn reject_unknown_profile() {
+        let config: ApplicationConfig = toml::from_str("profile = \"chip9\"").expect("failed to deserialize the config");
+
+        assert!(matches!(apply_profile(config), Err(ConfigError::UnknownProfile(_))), "failed to reject the unknown profile");
+    }
+}
@@ -0,0 +1,112 @@
+use crate::keypad::Keypad;
+
+/// actions that are triggered by hotkeys and handled by the frontend instead of the emulator
+pub enum Hotkey {
+    ToggleDebugOverlay,
+    ToggleMute,
+    ToggleInvertColors,
+    ToggleGrid,
+    ToggleStepMode,
+    ToggleSpriteDrawMode,
+    Step,
+    StepOver,
+    Reset,
+    DumpMemory,
+
+    /// the step key is held down (sent every frame, so the emulator keeps running in step mode)
+    KeepStepping,
+
+    /// a file was dropped onto the window
+    LoadRom(String),
+
+    /// the window gained (`true`) or lost (`false`) the input focus
+    FocusChanged(bool),
+}
+
+pub enum PollResult {
+    /// the keypad state for the next frame and the hotkeys that were triggered since the last poll
+    Input(Keypad, Vec<Hotkey>),
+
+    /// the application should quit
+    Quit,
+}
+
+/// a source of input for the emulator (e.g. the keyboard, a recorded replay or the network), which is polled once per frame
+///
+/// **NOTE:** the returned keypad should remember the key states of the previous poll (see `Keypad::advance_frame()`), so
+/// that presses and releases can be detected
+pub trait InputSource {
+    fn poll(&mut self) -> PollResult;
+}
+
+/// replays a fixed sequence of pressed keys (one entry per frame) and quits once all of them were polled
+pub struct ScriptedInputSource {
+    /// the IDs of the keys that are pressed in each frame
+    frames: Vec<Vec<u8>>,
+
+    next_frame_idx: usize,
+
+    keypad: Keypad,
+}
+
+impl ScriptedInputSource {
+    pub fn new(frames: Vec<Vec<u8>>) -> Self {
+        return ScriptedInputSource {
+            frames,
+            next_frame_idx: 0,
+            keypad: Keypad::new(),
+        };
+    }
+}
+
+impl InputSource for ScriptedInputSource {
+    fn poll(&mut self) -> PollResult {
+        let pressed_keys = match self.frames.get(self.next_frame_idx) {
+            Some(pressed_keys) => pressed_keys,
+            None => return PollResult::Quit,
+        };
+        self.next_frame_idx += 1;
+
+        self.keypad.advance_frame();
+        for key_id in 0..16 {
+            if pressed_keys.contains(&key_id) {
+                self.keypad.set_key(key_id);
+            } else {
+                self.keypad.unset_key(key_id);
+            }
+        }
+
+        return PollResult::Input(self.keypad.clone(), vec!());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::chip8::Chip8;
+    use crate::quirks::Quirks;
+
+    #[test]
+    fn drive_emulator_with_scripted_input() {
+        let mut chip8 = Chip8::new(Quirks::default());
+
+        // wait for a key and store it in V0
+        chip8.load_program(&[0xF0, 0x0A, 0x00, 0x00]).expect("failed to load the program");
+
+        let mut input_source = ScriptedInputSource::new(vec!(vec!(), vec!(), vec!(0xC, 0xB)));
+
+        let mut frames = 0;
+        while let PollResult::Input(keypad, _) = input_source.poll() {
+            assert!(!chip8.is_halted(), "failed to wait for the scripted key");
+
+            chip8.tick(keypad, Duration::from_nanos(1_000_000_000 / 60)).expect("an error occurred during emulator execution");
+            frames += 1;
+        }
+
+        // verify result
+        assert_eq!(frames, 3, "failed to quit after the last frame of the script");
+        assert_eq!(chip8.get_register(0x0), 0xB, "failed to read the scripted key");
+        assert!(chip8.is_halted(), "failed to run the program to the end");
+    }
+}
@@ -3,17 +3,51 @@ const NUM_KEYS: u32 = 16;
 #[derive(Clone)]
 pub struct Keypad {
     key_states: [bool; (NUM_KEYS as usize)],
+
+    /// key states of the previous frame (used for detecting presses and releases)
+    prev_key_states: [bool; (NUM_KEYS as usize)],
+
+    /// keys that were pressed at some point during the current frame (even if they were released again before the
+    /// frame is run), so that short taps aren't missed
+    latched_keys: [bool; (NUM_KEYS as usize)],
 }
 
 impl Keypad {
     pub fn new() -> Self {
         return Keypad {
             key_states: [false; (NUM_KEYS as usize)],
+            prev_key_states: [false; (NUM_KEYS as usize)],
+            latched_keys: [false; (NUM_KEYS as usize)],
         };
     }
 
+    /// creates a keypad with the keys of the set bits pressed (bit i = key i), e.g. from recorded input
+    ///
+    /// **NOTE:** no key was pressed during the previous frame
+    pub fn from_bitmask(bitmask: u16) -> Self {
+        let mut keypad = Keypad::new();
+        for key_id in 0..(NUM_KEYS as u8) {
+            keypad.key_states[key_id as usize] = (bitmask >> key_id) & 1 == 1;
+        }
+
+        return keypad;
+    }
+
+    /// returns the pressed keys as a bitmask (bit i = key i), which is the compact form for storing or sending input
+    pub fn to_bitmask(&self) -> u16 {
+        let mut bitmask: u16 = 0;
+        for (idx, key_state) in self.key_states.iter().enumerate() {
+            if *key_state {
+                bitmask |= 1 << idx;
+            }
+        }
+
+        return bitmask;
+    }
+
     pub fn set_key(&mut self, key_id: u8) {
         self.key_states[key_id as usize] = true;
+        self.latched_keys[key_id as usize] = true;
     }
 
     pub fn unset_key(&mut self, key_id: u8) {
@@ -24,7 +58,44 @@ impl Keypad {
         return self.key_states[key_id as usize];
     }
 
+    /// returns `true` if the key is pressed or was tapped during the current frame and the tap wasn't consumed yet (see
+    /// [`Keypad::consume_latch()`])
+    pub fn latch_pressed(&self, key_id: u8) -> bool {
+        return self.key_states[key_id as usize] || self.latched_keys[key_id as usize];
+    }
+
+    /// marks a tap as handled, so that it's only seen once (a held key stays pressed)
+    pub fn consume_latch(&mut self, key_id: u8) {
+        self.latched_keys[key_id as usize] = false;
+    }
+
+    /// like `get_keypress()`, but also returns a tapped key (and consumes its latch, see [`Keypad::latch_pressed()`])
+    pub fn take_latched_keypress(&mut self) -> Option<u8> {
+        let key_id = (0..(NUM_KEYS as u8)).find(|key_id| self.latch_pressed(*key_id))?;
+        self.consume_latch(key_id);
+
+        return Some(key_id);
+    }
+
+    /// returns `true` if the key is pressed now, but wasn't during the previous frame
+    pub fn was_just_pressed(&self, key_id: u8) -> bool {
+        return self.key_states[key_id as usize] && !self.prev_key_states[key_id as usize];
+    }
+
+    /// returns `true` if the key was pressed during the previous frame, but isn't anymore
+    pub fn was_just_released(&self, key_id: u8) -> bool {
+        return !self.key_states[key_id as usize] && self.prev_key_states[key_id as usize];
+    }
+
+    /// **NOTE:** should be executed once at the end of every frame (this also clears the latches of the frame)
+    pub fn advance_frame(&mut self) {
+        self.prev_key_states = self.key_states;
+        self.latched_keys = self.key_states;
+    }
+
     /// returns the first keypress, if available
+    ///
+    /// **NOTE:** if multiple keys are held, the one with the lowest index is returned
     pub fn get_keypress(&self) -> Option<u8> {
         for (idx, key_state) in self.key_states.iter().enumerate() {
             if *key_state {
@@ -33,4 +104,93 @@ impl Keypad {
         }
         return None;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_and_release_across_frames() {
+        let mut keypad = Keypad::new();
+
+        // frame 1: press key
+        keypad.set_key(0xA);
+
+        assert!(keypad.was_just_pressed(0xA), "failed to detect the key press");
+        assert!(!keypad.was_just_released(0xA), "falsely detected a key release");
+
+        keypad.advance_frame();
+
+        // frame 2: hold key
+        assert!(!keypad.was_just_pressed(0xA), "falsely detected a key press while the key is held");
+        assert!(keypad.check_key_state(0xA), "failed to keep the key pressed");
+
+        keypad.advance_frame();
+
+        // frame 3: release key
+        keypad.unset_key(0xA);
+
+        assert!(keypad.was_just_released(0xA), "failed to detect the key release");
+        assert!(!keypad.was_just_pressed(0xA), "falsely detected a key press");
+
+        keypad.advance_frame();
+
+        assert!(!keypad.was_just_released(0xA), "failed to clear the key release after advancing the frame");
+    }
+
+    #[test]
+    fn hold_multiple_keys() {
+        let mut keypad = Keypad::new();
+
+        // e.g. up + fire
+        let keys: [u8; 3] = [0x5, 0x2, 0xF];
+        for key_id in keys {
+            keypad.set_key(key_id);
+        }
+
+        for key_id in keys {
+            assert!(keypad.check_key_state(key_id), "failed to keep key 0x{:X} pressed", key_id);
+        }
+        assert_eq!(keypad.get_keypress(), Some(0x2), "failed to return the lowest held key");
+
+        // release one of the keys
+        keypad.unset_key(0x2);
+
+        assert!(!keypad.check_key_state(0x2), "failed to release the key");
+        assert!(keypad.check_key_state(0x5) && keypad.check_key_state(0xF), "failed to keep the other keys pressed");
+        assert_eq!(keypad.get_keypress(), Some(0x5), "failed to return the lowest held key after a release");
+    }
+
+    #[test]
+    fn bitmask_round_trip() {
+        for bitmask in 0..=u16::MAX {
+            assert_eq!(Keypad::from_bitmask(bitmask).to_bitmask(), bitmask, "failed to restore the bitmask 0x{:04X}", bitmask);
+        }
+
+        let keypad = Keypad::from_bitmask(0b1000_0000_0010_0001);
+        assert!(keypad.check_key_state(0x0) && keypad.check_key_state(0x5) && keypad.check_key_state(0xF), "failed to press the keys of the set bits");
+        assert_eq!(keypad.get_keypress(), Some(0x0), "failed to return the lowest held key");
+    }
+
+    #[test]
+    fn latch_tap_within_frame() {
+        let mut keypad = Keypad::new();
+
+        // press and release before the frame is run
+        keypad.set_key(0x7);
+        keypad.unset_key(0x7);
+
+        assert!(!keypad.check_key_state(0x7), "falsely kept the released key pressed");
+        assert!(keypad.latch_pressed(0x7), "failed to latch the tapped key");
+        assert_eq!(keypad.take_latched_keypress(), Some(0x7), "failed to return the tapped key");
+        assert!(!keypad.latch_pressed(0x7), "failed to consume the latch");
+
+        // the latch doesn't outlast the frame
+        keypad.set_key(0x7);
+        keypad.unset_key(0x7);
+        keypad.advance_frame();
+
+        assert!(!keypad.latch_pressed(0x7), "failed to clear the latch at the end of the frame");
+    }
+}
@@ -0,0 +1,43 @@
+//! the emulator core, which is independent of the SDL frontend (the binary)
+//!
+//! **NOTE:** without the default `std` feature the core is `no_std` (it only needs `alloc`), so it can run on a
+//! microcontroller:
+//!  - programs are loaded from byte slices (`Chip8::load_rom_bytes()`) instead of files
+//!  - the time is passed in by the caller (`Chip8::advance()` / `Chip8::run_frame()`), the core never reads a clock
+//!  - diagnostics only go through the `log` facade, nothing is printed
+//!  - save-states, the RPL flag files and the audio pattern rate need `std`
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod chip8;
+pub mod disassembler;
+pub mod keypad;
+pub mod quirks;
+pub mod save_state;
+pub mod screen;
+pub mod stack;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use core::time::Duration;
+    use crate::chip8::{Chip8, RunOutcome};
+    use crate::quirks::Quirks;
+
+    // only uses what is available without `std`, so `cargo test --lib --no-default-features` checks the bare-metal build
+    #[test]
+    fn run_core_without_std() {
+        let mut chip8 = Chip8::new(Quirks::default());
+
+        // LD V0, 0x05; LD F, V0; DRW V0, V0, 5; JP 0x206
+        let rom = vec![0x60, 0x05, 0xF0, 0x29, 0xD0, 0x05, 0x12, 0x06];
+        chip8.load_rom_bytes(&rom).expect("failed to load the program");
+
+        let outcome = chip8.run_frame(Duration::from_millis(16)).expect("failed to run a frame");
+        assert_eq!(outcome, RunOutcome::Continue, "failed to run a complete frame");
+        assert!(chip8.instructions_executed() >= 4, "failed to execute the program");
+        assert!(chip8.frame().pixels().iter().any(|pixel| *pixel), "failed to draw the sprite");
+    }
+}
@@ -1,46 +1,128 @@
-extern crate core;
-
-pub mod stack;
-pub mod screen;
 mod config;
-mod chip8;
-mod keypad;
-
-use std::fs::File;
-use std::io::{self, Read};
+mod cli;
+mod overlay;
+mod assembler;
+mod selftest;
+mod quirk_test;
+mod input;
+
+// the emulator core is the library of this package, the modules of the frontend refer to it via `crate::<module>`
+use chip8_core::{chip8, disassembler, keypad, quirks, screen, stack};
+
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Read};
 use std::path::Path;
 use std::time::{Duration, Instant};
 use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::render::{WindowCanvas};
 use sdl2::{EventPump, Sdl};
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::messagebox::{self, MessageBoxFlag};
 use sdl2::rect::Rect;
-use confy;
-use chip8::Chip8;
+use log::{error, info};
+use chip8::{Chip8, RunOutcome};
 use keypad::Keypad;
+use input::{Hotkey, InputSource, PollResult};
 use crate::config::ApplicationConfig;
+use crate::screen::{Palette, SpriteDrawMode};
 
 // GUI constants
 const FPS: u64 = 60;
+const WINDOW_TITLE: &str = "CHIP-8 emulator";
+
+/// file extension of programs that are assembled on load instead of being loaded as binary
+const ASSEMBLY_FILE_EXTENSION: &str = "8o";
+const BACKGROUND_COLOR: Color = Color::BLACK;
+
+/// grid lines are only drawn from this screen scale on (below, they would cover most of the pixels)
+const MIN_GRID_SCALE: u32 = 3;
 
 fn main() -> Result<(), ApplicationError> {
+    // lifecycle messages are shown by default; `RUST_LOG` adjusts the verbosity (e.g. `RUST_LOG=chip8=trace`)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    // load config
-    let config = config::load_config().map_err(|err| ApplicationError::Config(err))?;
+    let mut cli_args = cli::parse_args(std::env::args().skip(1)).map_err(|err| ApplicationError::Cli(err))?;
+    cli_args.no_prompt |= std::env::var_os(cli::NONINTERACTIVE_ENV_VAR).is_some();
 
-    let mut chip8 = Chip8::new(true, true, false);
+    // load config (only ask for user input on an interactive terminal)
+    let interactive = !cli_args.headless && !cli_args.no_prompt && io::stdin().is_terminal();
+    let mut config = if !interactive {
+        config::load_config_or_default()
+    } else {
+        config::load_config()
+    }.map_err(|err| ApplicationError::Config(err))?;
+
+    // command line arguments take precedence over the config file (but aren't stored in it)
+    cli_args.apply_to_config(&mut config);
+
+    if cli_args.quirk_report {
+        return print_quirk_report(config.quirks);
+    }
+
+    let mut chip8 = Chip8::new(config.quirks);
+    chip8.seed_random_generator(rand::random());
+    chip8.set_stack_depth(config.stack_depth);
+    chip8.set_max_instructions_per_frame(config.max_instructions_per_frame);
+    chip8.set_instruction_costs(config.instruction_costs);
+    if let Some(instructions_per_second) = config.instructions_per_second {
+        chip8.set_cycles_per_frame(((instructions_per_second as u64 / FPS) as u32).max(1));
+    }
+    chip8.set_detect_code_writes(config.warn_on_code_writes);
+    chip8.set_memory_pattern(config.memory_pattern);
+    if cli_args.log_flags {
+        chip8.set_flag_hook(Some(Box::new(|flag_change| {
+            info!("0x{:04X}: {:04X} set VF from {} to {}", flag_change.address, flag_change.opcode, flag_change.old_value, flag_change.new_value);
+        })));
+    }
+
+    let rpl_flags_path = config.persist_rpl_flags.then(|| config.rpl_flags_path.clone());
+    if let Some(rpl_flags_path) = &rpl_flags_path {
+        chip8.load_rpl_flags(Path::new(rpl_flags_path)).map_err(|err| ApplicationError::Chip8(err))?;
+    }
 
     // load fonts data
     let font_data: Vec<u8> = load_binary_file(&config.font_path)?;
     chip8.load_font(&font_data).map_err(|err| ApplicationError::Chip8(err))?;
+    if !config.big_font_path.is_empty() {
+        let big_font_data: Vec<u8> = load_binary_file(&config.big_font_path)?;
+        chip8.load_big_font(&big_font_data).map_err(|err| ApplicationError::Chip8(err))?;
+    }
 
     // load program
-    let program_data: Vec<u8> = load_binary_file(&config.program_path)?;
-    chip8.load_program(&program_data).map_err(|err| ApplicationError::Chip8(err))?;
+    if cli_args.selftest {
+        chip8.load_program(&selftest::program()).map_err(|err| ApplicationError::Chip8(err))?;
+    } else if cli_args.rom_path.as_deref() == Some(cli::STDIN_PATH) {
+        chip8.load_rom_from_reader(io::stdin()).map_err(|err| ApplicationError::Chip8(err))?;
+    } else {
+        load_program_file(&mut chip8, &config.program_path)?;
+    }
+
+    // apply the recommended quirks for known programs and the quirks configured for the program
+    let rom_path = (!cli_args.selftest && cli_args.rom_path.as_deref() != Some(cli::STDIN_PATH)).then(|| config.program_path.as_str());
+    let quirks = config.quirks_for_rom(rom_path, chip8.get_rom_data());
+    chip8.set_quirks(quirks);
+
+    let result = if cli_args.headless {
+        run_headless(&mut chip8, cli_args.frames)
+    } else {
+        run_window(&mut chip8, config, cli_args.selftest)
+    };
+
+    // show what led to the error
+    if let Err(ApplicationError::Chip8(_)) = result {
+        print_recent_instructions(&chip8);
+    }
 
-    run(&mut chip8, config)?;
+    if let Some(rpl_flags_path) = &rpl_flags_path {
+        chip8.save_rpl_flags(Path::new(rpl_flags_path)).map_err(|err| ApplicationError::Chip8(err))?;
+    }
+    result?;
+
+    if let Some(dump_screen_path) = &cli_args.dump_screen_path {
+        dump_screen(&chip8, dump_screen_path)?;
+    }
 
     return Ok(());
 }
@@ -55,50 +137,245 @@ fn load_binary_file(path: &str) -> Result<Vec<u8>, ApplicationError> {
     return Ok(data);
 }
 
+/// loads a binary program or assembles the source of one (see [`ASSEMBLY_FILE_EXTENSION`])
+fn load_program_file(chip8: &mut Chip8, path: &str) -> Result<(), ApplicationError> {
+    let path = Path::new(path);
+
+    if path.extension().map_or(false, |extension| extension == ASSEMBLY_FILE_EXTENSION) {
+        let src = fs::read_to_string(path).map_err(|err| ApplicationError::IO(err))?;
+        let program_data = assembler::assemble_at(&src, chip8.get_program_start_address()).map_err(|err| ApplicationError::Assembler(err))?;
+        chip8.load_program(&program_data).map_err(|err| ApplicationError::Chip8(err))?;
+    } else {
+        chip8.load_rom_from_file(path).map_err(|err| ApplicationError::Chip8(err))?;
+    }
+
+    return Ok(());
+}
+
+fn dump_screen(chip8: &Chip8, path: &str) -> Result<(), ApplicationError> {
+    let ascii = chip8.frame().to_ascii();
+
+    if path == cli::STDOUT_PATH {
+        print!("{}", ascii);
+    } else {
+        fs::write(Path::new(path), ascii).map_err(|err| ApplicationError::IO(err))?;
+    }
+
+    return Ok(());
+}
+
 #[derive(Debug)]
 enum ApplicationError {
+    Cli(String),
     Sdl(String),
     Chip8(chip8::Chip8Error),
-    Config(confy::ConfyError),
+    Assembler(assembler::AssembleError),
+    Config(config::ConfigError),
     IO(io::Error),
 }
 
+/// a mono sound source that is sampled by the `AudioMixer`
+trait ToneGenerator {
+    fn next_sample(&mut self) -> f32;
+
+    /// returns if the release ramp has ended (so the audio device can be paused without a click)
+    fn is_silent(&self) -> bool;
+}
+
+/// fades a tone in and out over a short ramp (switching the volume abruptly makes a click)
+struct Envelope {
+    /// current level (`0.0` = silent; `1.0` = full volume)
+    level: f32,
+
+    /// change of the level per sample (`1.0` = no ramp)
+    step: f32,
+}
+
+impl Envelope {
+    fn new(sample_rate: i32, ramp_duration: Duration) -> Self {
+        let ramp_samples = ramp_duration.as_secs_f32() * sample_rate as f32;
+
+        return Envelope {
+            level: 0.0,
+            step: if ramp_samples > 1.0 { 1.0 / ramp_samples } else { 1.0 },
+        };
+    }
+
+    /// ramps the level up if `on` is set and down otherwise, then returns it
+    fn next_level(&mut self, on: bool) -> f32 {
+        self.level = if on {
+            (self.level + self.step).min(1.0)
+        } else {
+            (self.level - self.step).max(0.0)
+        };
+
+        return self.level;
+    }
+}
+
+/// the classic beep (a fixed tone)
 struct SquareWave {
     phase_inc: f32,
     phase: f32,
     volume: f32,
+    envelope: Envelope,
+
+    /// the envelope ramps up while this is set and down otherwise
+    on: bool,
 }
 
-impl AudioCallback for SquareWave {
+impl SquareWave {
+    fn new(frequency: f32, sample_rate: i32, volume: f32, ramp_duration: Duration) -> Self {
+        return SquareWave {
+            phase_inc: frequency / sample_rate as f32,
+            phase: 0.0,
+            volume,
+            envelope: Envelope::new(sample_rate, ramp_duration),
+            on: false,
+        };
+    }
+}
+
+impl ToneGenerator for SquareWave {
+    fn next_sample(&mut self) -> f32 {
+        let level = self.envelope.next_level(self.on);
+
+        // Generate a square wave
+        let sample = if self.phase <= 0.5 {
+            self.volume
+        } else {
+            -self.volume
+        };
+        self.phase = (self.phase + self.phase_inc) % 1.0;
+
+        return sample * level;
+    }
+
+    fn is_silent(&self) -> bool {
+        return !self.on && self.envelope.level == 0.0;
+    }
+}
+
+/// plays the XO-CHIP audio pattern (1 bit per sample period, see [`Chip8::get_audio_pattern()`])
+struct PatternPlayer {
+    sample_rate: f32,
+    volume: f32,
+    envelope: Envelope,
+
+    pattern: [u8; chip8::AUDIO_PATTERN_SIZE],
+
+    /// position in the pattern in bits
+    position: f32,
+
+    /// pattern bits per sample (playback rate of the pattern divided by the sample rate)
+    step: f32,
+
+    /// the envelope ramps up while this is set and down otherwise
+    on: bool,
+}
+
+impl PatternPlayer {
+    fn new(sample_rate: i32, volume: f32, ramp_duration: Duration) -> Self {
+        return PatternPlayer {
+            sample_rate: sample_rate as f32,
+            volume,
+            envelope: Envelope::new(sample_rate, ramp_duration),
+            pattern: [0; chip8::AUDIO_PATTERN_SIZE],
+            position: 0.0,
+            step: 0.0,
+            on: false,
+        };
+    }
+
+    /// plays the pattern at the given rate (in bits per second)
+    fn set_pattern(&mut self, pattern: [u8; chip8::AUDIO_PATTERN_SIZE], rate: f32) {
+        self.pattern = pattern;
+        self.step = rate / self.sample_rate;
+    }
+}
+
+impl ToneGenerator for PatternPlayer {
+    fn next_sample(&mut self) -> f32 {
+        let level = self.envelope.next_level(self.on);
+
+        // play the bits of the pattern from the most significant bit of the first byte on (and loop)
+        let bit_idx = self.position as usize;
+        let high = (self.pattern[bit_idx / 8] >> (7 - bit_idx % 8)) & 1 == 1;
+        self.position = (self.position + self.step) % (self.pattern.len() * 8) as f32;
+
+        let sample = if high {
+            self.volume
+        } else {
+            -self.volume
+        };
+
+        return sample * level;
+    }
+
+    fn is_silent(&self) -> bool {
+        return !self.on && self.envelope.level == 0.0;
+    }
+}
+
+/// mixes the buzzer with the sample channel and pans the result if the device is stereo
+struct AudioMixer {
+    buzzer: SquareWave,
+
+    /// the XO-CHIP audio pattern, kept separate from the buzzer so each channel fades on its own when a program
+    /// switches between them
+    sample_channel: PatternPlayer,
+
+    /// number of interleaved output channels (1 = mono; 2 = stereo)
+    channels: u8,
+
+    /// `-1.0` = left only; `0.0` = center; `1.0` = right only (ignored for mono)
+    balance: f32,
+}
+
+impl AudioMixer {
+    fn is_silent(&self) -> bool {
+        return self.buzzer.is_silent() && self.sample_channel.is_silent();
+    }
+}
+
+impl AudioCallback for AudioMixer {
     type Channel = f32;
 
     fn callback(&mut self, out: &mut [f32]) {
-        // Generate a square wave
-        for x in out.iter_mut() {
-            *x = if self.phase <= 0.5 {
-                self.volume
+        // linear panning: the louder side keeps its full volume
+        let left_gain = (1.0 - self.balance).min(1.0);
+        let right_gain = (1.0 + self.balance).min(1.0);
+
+        for frame in out.chunks_mut(self.channels as usize) {
+            let sample = self.buzzer.next_sample() + self.sample_channel.next_sample();
+
+            if frame.len() >= 2 {
+                frame[0] = sample * left_gain;
+                frame[1] = sample * right_gain;
             } else {
-                -self.volume
-            };
-            self.phase = (self.phase + self.phase_inc) % 1.0;
+                frame[0] = sample;
+            }
         }
     }
 }
 
-fn init_audio_device(sdl_context: &Sdl) -> Result<AudioDevice<SquareWave>, ApplicationError> {
+fn init_audio_device(sdl_context: &Sdl, config: &ApplicationConfig) -> Result<AudioDevice<AudioMixer>, ApplicationError> {
     let audio_subsystem = sdl_context.audio().map_err(|err| ApplicationError::Sdl(err))?;
 
     let audio_device_spec = AudioSpecDesired {
         freq: Some(44_100),
-        channels: Some(1), // mono
+        channels: Some(if config.stereo_audio { 2 } else { 1 }),
         samples: None,
     };
 
     let audio_device = audio_subsystem.open_playback(None, &audio_device_spec, |spec| {
-        SquareWave {
-            phase_inc: 440.0 / spec.freq as f32,
-            phase: 0.0,
-            volume: 0.05,
+        let ramp_duration = Duration::from_secs_f32(config.audio_ramp_ms.max(0.0) / 1000.0);
+
+        AudioMixer {
+            buzzer: SquareWave::new(440.0, spec.freq, 0.05, ramp_duration),
+            sample_channel: PatternPlayer::new(spec.freq, 0.05, ramp_duration),
+            channels: spec.channels,
+            balance: config.audio_balance.clamp(-1.0, 1.0),
         }
     }).map_err(|err| ApplicationError::Sdl(err))?;
 
@@ -109,17 +386,19 @@ fn init_canvas(sdl_context: &Sdl, screen_scale: u32) -> Result<WindowCanvas, App
     let video_subsystem = sdl_context.video().map_err(|err| ApplicationError::Sdl(err))?;
 
     let window = video_subsystem
-        .window("CHIP-8 emulator", screen::WIDTH * screen_scale, screen::HEIGHT * screen_scale)
+        .window(WINDOW_TITLE, screen::WIDTH * screen_scale, screen::HEIGHT * screen_scale)
         .position_centered()
+        .resizable()
         .build()
         .map_err(|err| ApplicationError::Sdl(err.to_string()))?;
 
     let mut canvas = window
         .into_canvas()
+        .present_vsync() // prevents tearing; `canvas.present()` blocks until the next vertical blank
         .build()
         .map_err(|err| ApplicationError::Sdl(err.to_string()))?;
 
-    canvas.set_draw_color(Color::BLACK);
+    canvas.set_draw_color(BACKGROUND_COLOR);
     canvas.clear();
     canvas.present();
 
@@ -132,7 +411,35 @@ fn init_event_pump(sdl_context: &Sdl) -> Result<EventPump, ApplicationError> {
     return Ok(event_pump);
 }
 
-fn get_input(event_pump: &mut EventPump, keypad: &mut Keypad) -> Result<(), ()> {
+/// reads the keypad and the hotkeys from the keyboard and the window events
+struct SdlInputSource {
+    event_pump: EventPump,
+
+    keypad: Keypad,
+}
+
+impl SdlInputSource {
+    fn new(sdl_context: &Sdl) -> Result<Self, ApplicationError> {
+        return Ok(SdlInputSource {
+            event_pump: init_event_pump(sdl_context)?,
+            keypad: Keypad::new(),
+        });
+    }
+}
+
+impl InputSource for SdlInputSource {
+    fn poll(&mut self) -> PollResult {
+        self.keypad.advance_frame();
+
+        return match get_input(&mut self.event_pump, &mut self.keypad) {
+            Ok(hotkeys) => PollResult::Input(self.keypad.clone(), hotkeys),
+            Err(()) => PollResult::Quit,
+        };
+    }
+}
+
+/// returns the hotkeys that were pressed, or `Err` if the application should quit
+fn get_input(event_pump: &mut EventPump, keypad: &mut Keypad) -> Result<Vec<Hotkey>, ()> {
     // original keypad
     // 1 2 3 C
     // 4 5 6 D
@@ -145,12 +452,35 @@ fn get_input(event_pump: &mut EventPump, keypad: &mut Keypad) -> Result<(), ()>
     // A S D F
     // Z X C V (Z can also be Y)
 
+    // hotkeys
+    // F1: toggle debug overlay
+    // M: toggle mute
+    // I: toggle inverted colors
+    // G: toggle grid lines
+    // F9: toggle step mode
+    // Space: execute the next instruction (step mode only; hold to keep running)
+    // F10: execute the next instruction, running called subroutines up to their return (step mode only)
+    // F5: reset the emulator (also continues after an error)
+    // F2: toggle between drawing sprites with XOR (real behaviour) and OR (debugging)
+    // F6: write the memory to a file (named after the number of executed instructions)
+    // dropping a file onto the window loads it as the new program
+
+    let mut hotkeys: Vec<Hotkey> = vec!();
+
     for event in event_pump.poll_iter() {
         match event {
             Event::Quit { .. } | Event::KeyDown {
                 keycode: Some(Keycode::Escape),
                 ..
             } => return Err(()),
+            Event::KeyDown {
+                keycode: Some(Keycode::Space),
+                repeat: false,
+                ..
+            } => hotkeys.push(Hotkey::Step),
+            Event::DropFile { filename, .. } => hotkeys.push(Hotkey::LoadRom(filename)),
+            Event::Window { win_event: WindowEvent::FocusGained, .. } => hotkeys.push(Hotkey::FocusChanged(true)),
+            Event::Window { win_event: WindowEvent::FocusLost, .. } => hotkeys.push(Hotkey::FocusChanged(false)),
             Event::KeyDown {
                 keycode: Some(keycode),
                 ..
@@ -172,6 +502,15 @@ fn get_input(event_pump: &mut EventPump, keypad: &mut Keypad) -> Result<(), ()>
                     Keycode::X => keypad.set_key(0x0),
                     Keycode::C => keypad.set_key(0xB),
                     Keycode::V => keypad.set_key(0xF),
+                    Keycode::F1 => hotkeys.push(Hotkey::ToggleDebugOverlay),
+                    Keycode::M => hotkeys.push(Hotkey::ToggleMute),
+                    Keycode::I => hotkeys.push(Hotkey::ToggleInvertColors),
+                    Keycode::G => hotkeys.push(Hotkey::ToggleGrid),
+                    Keycode::F9 => hotkeys.push(Hotkey::ToggleStepMode),
+                    Keycode::F10 => hotkeys.push(Hotkey::StepOver),
+                    Keycode::F5 => hotkeys.push(Hotkey::Reset),
+                    Keycode::F2 => hotkeys.push(Hotkey::ToggleSpriteDrawMode),
+                    Keycode::F6 => hotkeys.push(Hotkey::DumpMemory),
                     _ => {}
                 }
             },
@@ -203,76 +542,463 @@ fn get_input(event_pump: &mut EventPump, keypad: &mut Keypad) -> Result<(), ()>
         }
     }
 
-    return Ok(());
+    // holding the space bar keeps the emulator running in step mode
+    if event_pump.keyboard_state().is_scancode_pressed(Scancode::Space) {
+        hotkeys.push(Hotkey::KeepStepping);
+    }
+
+    return Ok(hotkeys);
 }
 
-fn update_audio_device(audio_device: &AudioDevice<SquareWave>, chip8: &Chip8) {
-    if chip8.playing_sound() {
-        audio_device.resume();
-    } else {
+/// returns the address and mnemonic of the instruction at the program counter (e.g. `0x0204 JP 0x208`)
+fn get_next_instruction_mnemonic(chip8: &Chip8) -> String {
+    return match chip8.peek_next_instruction() {
+        Some((address, opcode)) => format!("0x{:04X} {}", address, disassembler::disassemble(opcode)),
+        None => String::from("end of memory"),
+    };
+}
+
+/// prints the error together with the instruction that caused it, the ones that led there and the machine state
+fn report_error(chip8: &Chip8, err: &chip8::Chip8Error) {
+    error!("the emulator stopped because of an error: {}", err);
+    if let Some((address, opcode)) = chip8.recent_instructions().last() {
+        error!("failed instruction: 0x{:04X}: {:04X} {}", address, opcode, disassembler::disassemble(*opcode));
+    }
+    print_recent_instructions(chip8);
+    info!("state:\n{}", chip8.dump_state_text().trim_end());
+}
+
+/// logs the last executed instructions (e.g. to show what led to an error)
+fn print_recent_instructions(chip8: &Chip8) {
+    info!("last executed instructions:");
+    for (address, opcode) in chip8.recent_instructions() {
+        info!("0x{:04X}: {:04X} {}", address, opcode, disassembler::disassemble(opcode));
+    }
+}
+
+/// starts or stops the beep (or the XO-CHIP audio pattern of the program); the device keeps running until the release
+/// ramp has ended
+fn update_audio_device(audio_device: &mut AudioDevice<AudioMixer>, beep: bool, chip8: &Chip8) {
+    let silent = {
+        let mut audio_mixer = audio_device.lock();
+
+        // the audio pattern replaces the beep once the program loaded one
+        let pattern = chip8.get_audio_pattern();
+        if let Some(pattern) = pattern {
+            audio_mixer.sample_channel.set_pattern(pattern, chip8.get_audio_pattern_rate());
+        }
+        audio_mixer.buzzer.on = beep && pattern.is_none();
+        audio_mixer.sample_channel.on = beep && pattern.is_some();
+
+        audio_mixer.is_silent()
+    };
+
+    if silent {
         audio_device.pause();
+    } else {
+        audio_device.resume();
     }
 }
 
-fn update_screen(canvas: &mut WindowCanvas, chip8: &Chip8, screen_scale: u32) {
-    let frame_buffer = chip8.get_frame_buffer();
+/// converts the RGB colors of the config to the palette the pixels are drawn with (inverting swaps the background and the
+/// first plane color)
+fn get_palette(colors: &[[u8; 3]; 4], invert_colors: bool) -> Palette {
+    let mut palette: Palette = colors.map(|[r, g, b]| [r, g, b, 0xFF]);
+    if invert_colors {
+        palette.swap(0, 1);
+    }
 
-    for (y_pos, row) in frame_buffer.iter().enumerate() {
-        for (x_pos, pixel_val) in row.iter().enumerate() {
-            let color = if *pixel_val { Color::WHITE } else { Color::BLACK };
+    return palette;
+}
 
-            let real_x_pos = (x_pos as u32) * screen_scale;
-            let real_y_pos = (y_pos as u32) * screen_scale;
+/// draws the screen into the window and returns the area it was drawn to (see [`get_screen_rect()`])
+fn update_screen(canvas: &mut WindowCanvas, chip8: &Chip8, integer_scale: bool, palette: &Palette) -> Rect {
+    let frame = chip8.frame();
+    let rgba = frame.to_rgba_with_palette(Some(chip8.second_plane()), palette, 1);
 
-            let rect = Rect::new(real_x_pos as i32, real_y_pos as i32, screen_scale, screen_scale);
+    // the texture is scaled up by SDL when it's copied onto the canvas
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGBA32, frame.width(), frame.height()).unwrap();
+    texture.update(None, &rgba, (frame.width() * 4) as usize).unwrap();
 
-            canvas.set_draw_color(color);
-            canvas.fill_rect(rect).unwrap();
-        }
+    // fill the margins around the screen
+    let background_color = palette[0];
+    canvas.set_draw_color(Color::RGB(background_color[0], background_color[1], background_color[2]));
+    canvas.clear();
+
+    let (window_width, window_height) = canvas.output_size().unwrap();
+    let rect = get_screen_rect(frame.width(), frame.height(), window_width, window_height, integer_scale);
+    canvas.copy(&texture, None, rect).unwrap();
+
+    return rect;
+}
+
+/// returns the area of the window the screen is drawn to
+///  - integer scale: the largest integer multiple of the resolution that fits the window (at least 1), centered
+///  - otherwise: the whole window (the pixels are stretched if the aspect ratio differs)
+fn get_screen_rect(width: u32, height: u32, window_width: u32, window_height: u32, integer_scale: bool) -> Rect {
+    if !integer_scale {
+        return Rect::new(0, 0, window_width, window_height);
     }
 
-    canvas.present();
+    let scale = (window_width / width).min(window_height / height).max(1);
+    let (scaled_width, scaled_height) = (width * scale, height * scale);
+
+    let x_pos = (window_width as i32 - scaled_width as i32) / 2;
+    let y_pos = (window_height as i32 - scaled_height as i32) / 2;
+
+    return Rect::new(x_pos, y_pos, scaled_width, scaled_height);
+}
+
+/// returns the grid lines between the scaled pixels (none if the scale is too small for them to be visible)
+///
+/// **NOTE:** the lines are drawn on the left and top edge of each pixel, so the pixels stay in place and the lines never
+/// exceed the game area
+fn get_grid_lines(width: u32, height: u32, screen_scale: u32) -> Vec<Rect> {
+    if screen_scale < MIN_GRID_SCALE {
+        return vec!();
+    }
+
+    let vertical_lines = (1..width).map(|x_pos| Rect::new((x_pos * screen_scale) as i32, 0, 1, height * screen_scale));
+    let horizontal_lines = (1..height).map(|y_pos| Rect::new(0, (y_pos * screen_scale) as i32, width * screen_scale, 1));
+
+    return vertical_lines.chain(horizontal_lines).collect();
 }
 
-fn run(chip8: &mut Chip8, config: ApplicationConfig) -> Result<(), ApplicationError> {
+/// draws the grid over the screen drawn at `screen_rect`
+///
+/// **NOTE:** nothing is drawn if the screen is stretched by a non-integer factor (the lines wouldn't be evenly spaced)
+fn draw_grid(canvas: &mut WindowCanvas, chip8: &Chip8, screen_rect: Rect, grid_color: Color) {
+    let frame = chip8.frame();
+    if screen_rect.width() % frame.width() != 0 || screen_rect.height() % frame.height() != 0
+        || screen_rect.width() / frame.width() != screen_rect.height() / frame.height() {
+        return;
+    }
+
+    let grid_lines: Vec<Rect> = get_grid_lines(frame.width(), frame.height(), screen_rect.width() / frame.width())
+        .into_iter()
+        .map(|line| Rect::new(line.x() + screen_rect.x(), line.y() + screen_rect.y(), line.width(), line.height()))
+        .collect();
+
+    canvas.set_draw_color(grid_color);
+    canvas.fill_rects(&grid_lines).unwrap();
+}
+
+/// runs the quirk test with the configured quirks and prints which quirks were observed
+fn print_quirk_report(quirks: quirks::Quirks) -> Result<(), ApplicationError> {
+    let report = quirk_test::run(quirks).map_err(|err| ApplicationError::Chip8(err))?;
+    println!("{:#?}", report);
+
+    let presets = report.matching_presets();
+    if presets.is_empty() {
+        println!("matches none of the presets");
+    } else {
+        println!("matches: {}", presets.join(", "));
+    }
+
+    return Ok(());
+}
+
+/// runs the emulator in a window with the input coming from the keyboard
+fn run_window(chip8: &mut Chip8, config: ApplicationConfig, selftest: bool) -> Result<(), ApplicationError> {
     let sdl_context = sdl2::init().map_err(|err| ApplicationError::Sdl(err))?;
+    let mut input_source = SdlInputSource::new(&sdl_context)?;
 
-    let audio_device = init_audio_device(&sdl_context)?;
-    let mut canvas = init_canvas(&sdl_context, config.screen_scale)?;
-    let mut event_pump = init_event_pump(&sdl_context)?;
+    return run(chip8, config, selftest, &sdl_context, &mut input_source);
+}
+
+/// runs the emulator in a window, with the input coming from `input_source` (e.g. an [`SdlInputSource`])
+///
+/// **NOTE:** in the self test, the colors of the palette are cycled every 2 seconds
+fn run<I: InputSource>(chip8: &mut Chip8, config: ApplicationConfig, selftest: bool, sdl_context: &Sdl, input_source: &mut I) -> Result<(), ApplicationError> {
+    let mut audio_device = init_audio_device(sdl_context, &config)?;
+    let mut canvas = init_canvas(sdl_context, config.screen_scale)?;
 
     let frame_duration = Duration::from_nanos(1_000_000_000 / FPS);
     let mut last_frame_timestamp = Instant::now();
 
-    let mut keypad = Keypad::new();
+    let mut show_debug_overlay = false;
+    let mut audio_muted = config.audio_muted;
+    let mut invert_colors = config.invert_colors;
+    let mut show_grid = config.show_grid;
+    let grid_color = Color::RGB(config.grid_color[0], config.grid_color[1], config.grid_color[2]);
+
+    // audio is paused while the window isn't focused (and the emulator as well if configured)
+    let mut focused = true;
+
+    // in step mode the emulator only runs while the space bar is pressed
+    let mut step_mode = false;
+
+    // after an error, the emulator is paused until it's reset
+    let mut error_paused = false;
 
     loop {
         // check if program has finished
-        if chip8.reached_end_of_file() {
+        if chip8.is_halted() {
+            info!("the program halted");
+            chip8.log_debug_info();
+            if config.print_state_on_halt {
+                print!("{}", chip8.dump_state_text());
+            }
             break;
         }
 
         // get input and load keypad
-        if let Ok(_) = get_input(&mut event_pump, &mut keypad) {
-            chip8.load_keypad(&keypad);
+        let mut step_requested = false;
+        let mut step_over_requested = false;
+        let mut keep_stepping = false;
+
+        let keypad = if let PollResult::Input(keypad, hotkeys) = input_source.poll() {
+            for hotkey in hotkeys {
+                match hotkey {
+                    Hotkey::ToggleDebugOverlay => show_debug_overlay = !show_debug_overlay,
+                    Hotkey::ToggleMute => audio_muted = !audio_muted,
+                    Hotkey::ToggleInvertColors => invert_colors = !invert_colors,
+                    Hotkey::ToggleGrid => show_grid = !show_grid,
+                    Hotkey::ToggleStepMode => {
+                        step_mode = !step_mode;
+                        if !step_mode {
+                            canvas.window_mut().set_title(WINDOW_TITLE).map_err(|err| ApplicationError::Sdl(err.to_string()))?;
+                        }
+                    },
+                    Hotkey::ToggleSpriteDrawMode => {
+                        let draw_mode = match chip8.get_sprite_draw_mode() {
+                            SpriteDrawMode::Xor => SpriteDrawMode::Or,
+                            SpriteDrawMode::Or => SpriteDrawMode::Xor,
+                        };
+                        info!("sprite draw mode: {:?}", draw_mode);
+                        chip8.set_sprite_draw_mode(draw_mode);
+                    },
+                    Hotkey::Step => step_requested = step_mode,
+                    Hotkey::StepOver => step_over_requested = step_mode,
+                    Hotkey::KeepStepping => keep_stepping = step_mode,
+                    Hotkey::Reset => {
+                        chip8.reset();
+                        error_paused = false;
+                        canvas.window_mut().set_title(WINDOW_TITLE).map_err(|err| ApplicationError::Sdl(err.to_string()))?;
+                    },
+                    Hotkey::DumpMemory => {
+                        let path = format!("./memory_{}.bin", chip8.instructions_executed());
+                        match chip8.dump_memory(Path::new(&path)) {
+                            Ok(()) => info!("dumped the memory to '{}'", path),
+                            Err(err) => error!("failed to dump the memory: {:?}", err),
+                        }
+                    },
+                    Hotkey::FocusChanged(is_focused) => focused = is_focused,
+                    Hotkey::LoadRom(path) => {
+                        // keep running the old program if the new one can't be loaded
+                        if let Err(err) = load_dropped_rom(chip8, &path, &config) {
+                            let message = format!("failed to load '{}': {:?}", path, err);
+                            messagebox::show_simple_message_box(MessageBoxFlag::ERROR, WINDOW_TITLE, &message, canvas.window())
+                                .map_err(|err| ApplicationError::Sdl(err.to_string()))?;
+                        }
+                    },
+                }
+            }
+            keypad
         } else {
             break;
-        }
+        };
 
-        // run emulator
-        chip8.run_frame(frame_duration).map_err(|err| ApplicationError::Chip8(err))?;
+        // run emulator for the time that actually passed since the last frame, which keeps the CPU speed correct
+        // on displays that don't run at 60Hz
+        let frame_delta = last_frame_timestamp.elapsed();
+        last_frame_timestamp = Instant::now();
+
+        let result = if error_paused || (!focused && config.pause_on_focus_loss) {
+            Ok((RunOutcome::Continue, false))
+        } else if step_mode {
+            chip8.load_keypad(&keypad);
+
+            // a single press executes exactly one instruction, holding the space bar keeps the emulator running
+            let result = if step_requested {
+                chip8.step()
+            } else if step_over_requested {
+                chip8.step_over()
+            } else if keep_stepping {
+                chip8.advance(frame_delta)
+            } else {
+                Ok(RunOutcome::Continue)
+            };
+
+            // show the next instruction
+            let title = format!("{} - next: {}", WINDOW_TITLE, get_next_instruction_mnemonic(chip8));
+            canvas.window_mut().set_title(&title).map_err(|err| ApplicationError::Sdl(err.to_string()))?;
+
+            result.map(|outcome| (outcome, chip8.playing_sound()))
+        } else {
+            chip8.tick(keypad, frame_delta).map(|result| (result.outcome, result.beep))
+        };
+
+        // keep the window open for inspecting the error instead of quitting
+        let (outcome, beep) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                report_error(chip8, &err);
+                error_paused = true;
+
+                let title = format!("{} - error (F5: reset; Esc: quit)", WINDOW_TITLE);
+                canvas.window_mut().set_title(&title).map_err(|err| ApplicationError::Sdl(err.to_string()))?;
+
+                (RunOutcome::Continue, false)
+            },
+        };
+
+        // pause on breakpoints and watchpoints (a halted program is handled at the start of the next frame)
+        match outcome {
+            RunOutcome::BreakpointHit(address) => {
+                info!("breakpoint hit at 0x{:04X}", address);
+                step_mode = true;
+            },
+            RunOutcome::WatchpointHit(address) => {
+                info!("watchpoint hit: 0x{:04X} was written", address);
+                step_mode = true;
+            },
+            RunOutcome::ReachedEndOfFile => info!("reached the end of memory"),
+            RunOutcome::InstructionLimitReached => break,
+            RunOutcome::Continue | RunOutcome::Halted => {},
+        }
 
         // update audio device
-        update_audio_device(&audio_device, &chip8);
+        update_audio_device(&mut audio_device, beep && !audio_muted && focused, chip8);
 
         // update screen
-        update_screen(&mut canvas, &chip8, config.screen_scale);
+        let mut palette = get_palette(&config.palette, invert_colors);
+        if selftest {
+            palette.rotate_left(((chip8.frames_run() / (2 * FPS)) % 4) as usize);
+        }
+        let screen_rect = update_screen(&mut canvas, &chip8, config.integer_scale, &palette);
+        if show_grid {
+            draw_grid(&mut canvas, &chip8, screen_rect, grid_color);
+        }
+        if show_debug_overlay {
+            overlay::draw_debug_overlay(&mut canvas, &chip8, config.screen_scale);
+        }
+        canvas.present();
 
-        // wait for frame duration to pass
+        // the vsync already paces the loop, but displays with a refresh rate above 60Hz still need to be capped
         let sleep_duration = frame_duration.checked_sub(last_frame_timestamp.elapsed()).unwrap_or(Duration::new(0, 0));
         std::thread::sleep(sleep_duration);
-        last_frame_timestamp = Instant::now();
+    }
+
+    // persist the settings that can be toggled by hotkeys
+    if audio_muted != config.audio_muted || invert_colors != config.invert_colors || show_grid != config.show_grid {
+        config::update_config_file(|config| {
+            config.audio_muted = audio_muted;
+            config.invert_colors = invert_colors;
+            config.show_grid = show_grid;
+        }).map_err(|err| ApplicationError::Config(err))?;
+    }
+
+    return Ok(());
+}
+
+/// replaces the running program and restarts the emulator (the old program stays loaded if an error occurs)
+fn load_dropped_rom(chip8: &mut Chip8, path: &str, config: &ApplicationConfig) -> Result<(), ApplicationError> {
+    load_program_file(chip8, path)?;
+
+    // apply the recommended quirks for known programs and the quirks configured for the program
+    let quirks = config.quirks_for_rom(Some(path), chip8.get_rom_data());
+    chip8.set_quirks(quirks);
+
+    return Ok(());
+}
+
+/// runs the emulator for a fixed number of frames without a window, audio or input (e.g. for automated checks)
+fn run_headless(chip8: &mut Chip8, frames: u64) -> Result<(), ApplicationError> {
+    let frame_duration = Duration::from_nanos(1_000_000_000 / FPS);
+
+    for _ in 0..frames {
+        // check if program has finished
+        if chip8.is_halted() {
+            break;
+        }
+
+        chip8.run_frame(frame_duration).map_err(|err| ApplicationError::Chip8(err))?;
     }
 
     return Ok(());
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invert_palette() {
+        let colors = [[0, 0, 0], [255, 255, 255], [170, 170, 170], [85, 85, 85]];
+
+        let palette = get_palette(&colors, false);
+        assert_eq!(palette[1], [255, 255, 255, 255], "failed to draw the pixel in the first plane color");
+
+        let inverted_palette = get_palette(&colors, true);
+        assert_eq!(inverted_palette[0], [255, 255, 255, 255], "failed to invert the background color");
+        assert_eq!(inverted_palette[1], [0, 0, 0, 255], "failed to invert the pixel color");
+        assert_eq!(inverted_palette[3], [85, 85, 85, 255], "failed to keep the color of both planes");
+    }
+
+    #[test]
+    fn beep_envelope() {
+        // 2 ms at 44.1 kHz = 88.2 samples
+        let mut square_wave = SquareWave::new(440.0, 44_100, 0.05, Duration::from_millis(2));
+
+        square_wave.on = true;
+        let attack: Vec<f32> = (0..100).map(|_| square_wave.next_sample().abs()).collect();
+        assert!(attack[0] < 0.05 * 0.1, "failed to start the beep quietly");
+        assert!(attack.windows(2).all(|pair| pair[1] >= pair[0] && pair[1] - pair[0] < 0.05 * 0.1), "failed to ramp up the volume");
+        assert_eq!(attack[99], 0.05, "failed to reach the full volume after the attack");
+
+        square_wave.on = false;
+        let release: Vec<f32> = (0..100).map(|_| square_wave.next_sample().abs()).collect();
+        assert!(release.windows(2).all(|pair| pair[1] <= pair[0] && pair[0] - pair[1] < 0.05 * 0.1), "failed to ramp down the volume");
+        assert_eq!(release[99], 0.0, "failed to silence the beep after the release");
+        assert!(square_wave.is_silent(), "failed to report the end of the release");
+    }
+
+    #[test]
+    fn play_audio_pattern() {
+        // 4000 bits per second at 8 kHz: every bit lasts 2 samples
+        let mut pattern_player = PatternPlayer::new(8000, 0.05, Duration::ZERO);
+        let mut pattern = [0x00; chip8::AUDIO_PATTERN_SIZE];
+        pattern[0] = 0b1010_0000;
+        pattern_player.set_pattern(pattern, 4000.0);
+        pattern_player.on = true;
+
+        let samples: Vec<f32> = (0..(2 * 128 + 2)).map(|_| pattern_player.next_sample()).collect();
+        assert_eq!(&samples[0..6], &[0.05, 0.05, -0.05, -0.05, 0.05, 0.05], "failed to play the bits of the pattern");
+        assert_eq!(&samples[256..258], &[0.05, 0.05], "failed to loop the pattern");
+    }
+
+    #[test]
+    fn integer_scaled_screen_rect() {
+        // (resolution, window size, expected rect)
+        let cases = [
+            ((64, 32), (1280, 640), Rect::new(0, 0, 1280, 640)),
+            ((64, 32), (1300, 700), Rect::new(10, 30, 1280, 640)),
+            ((64, 32), (800, 600), Rect::new(16, 108, 768, 384)),
+            ((128, 64), (1300, 700), Rect::new(10, 30, 1280, 640)),
+            ((64, 32), (1920, 1080), Rect::new(0, 60, 1920, 960)),
+        ];
+
+        for ((width, height), (window_width, window_height), expected) in cases {
+            let rect = get_screen_rect(width, height, window_width, window_height, true);
+            assert_eq!(rect, expected, "failed to fit {}x{} into a {}x{} window", width, height, window_width, window_height);
+        }
+
+        assert_eq!(get_screen_rect(64, 32, 1300, 700, false), Rect::new(0, 0, 1300, 700), "failed to stretch the screen to the window");
+        assert_eq!(get_screen_rect(64, 32, 32, 16, true).width(), 64, "failed to keep a scale of at least 1");
+    }
+
+    #[test]
+    fn grid_lines_inside_game_area() {
+        let screen_scale = 10;
+        let grid_lines = get_grid_lines(64, 32, screen_scale);
+
+        assert_eq!(grid_lines.len(), 63 + 31, "failed to create a line between each pair of pixels");
+        for line in grid_lines {
+            assert!(line.right() <= 64 * screen_scale as i32 && line.bottom() <= 32 * screen_scale as i32, "failed to keep the grid line inside the game area: {:?}", line);
+        }
+
+        assert!(get_grid_lines(64, 32, 1).is_empty(), "failed to skip the grid at a screen scale of 1");
+    }
+}
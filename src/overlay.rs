@@ -0,0 +1,108 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, WindowCanvas};
+use crate::chip8::Chip8;
+
+/// width of a glyph in pixels
+const GLYPH_WIDTH: u32 = 3;
+
+/// height of a glyph in pixels
+const GLYPH_HEIGHT: u32 = 5;
+
+/// space between two glyphs (and two lines) in pixels
+const GLYPH_SPACING: u32 = 1;
+
+/// space between the overlay border and the text in pixels
+const PADDING: u32 = 2;
+
+const TEXT_COLOR: Color = Color::RGB(0, 255, 0);
+const BACKGROUND_COLOR: Color = Color::RGBA(0, 0, 0, 192);
+
+/// returns the 3x5 bitmap of a character (one byte per row; the 3 least-significant bits are the pixels)
+///  - only covers the characters used by the overlay, everything else is rendered as a blank
+fn get_glyph(character: char) -> [u8; 5] {
+    return match character {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        _ => [0; 5],
+    };
+}
+
+/// returns the lines of text shown by the overlay (all values are hexadecimal, except the achieved instructions per second)
+pub fn get_debug_lines(chip8: &Chip8) -> Vec<String> {
+    let mut lines: Vec<String> = vec!();
+
+    lines.push(format!("PC {:04X}  I {:04X}", chip8.get_program_counter(), chip8.get_index_reg()));
+    lines.push(format!("SP {:02X}  DT {:02X}  ST {:02X}", chip8.get_stack_pointer(), chip8.get_delay_timer(), chip8.get_sound_timer()));
+
+    // 4 registers per line
+    for first_reg_id in (0x0..0x10).step_by(4) {
+        let registers: Vec<String> = (first_reg_id..first_reg_id + 4)
+            .map(|reg_id| format!("V{:X} {:02X}", reg_id, chip8.get_register(reg_id)))
+            .collect();
+        lines.push(registers.join("  "));
+    }
+
+    lines.push(format!("IPS {:.0}", chip8.effective_ips()));
+
+    return lines;
+}
+
+/// draws the registers, PC, SP and timers on top of the current frame (call before `canvas.present()`)
+pub fn draw_debug_overlay(canvas: &mut WindowCanvas, chip8: &Chip8, screen_scale: u32) {
+    // size of a single glyph pixel
+    let pixel_size = (screen_scale / 5).max(1);
+
+    let lines = get_debug_lines(chip8);
+    let max_line_len = lines.iter().map(|line| line.len() as u32).max().unwrap_or(0);
+
+    // draw background
+    let background_width = (max_line_len * (GLYPH_WIDTH + GLYPH_SPACING) + 2 * PADDING) * pixel_size;
+    let background_height = ((lines.len() as u32) * (GLYPH_HEIGHT + GLYPH_SPACING) + 2 * PADDING) * pixel_size;
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(BACKGROUND_COLOR);
+    canvas.fill_rect(Rect::new(0, 0, background_width, background_height)).unwrap();
+    canvas.set_blend_mode(BlendMode::None);
+
+    // draw text
+    canvas.set_draw_color(TEXT_COLOR);
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let line_y_pos = PADDING + (line_idx as u32) * (GLYPH_HEIGHT + GLYPH_SPACING);
+
+        for (char_idx, character) in line.chars().enumerate() {
+            let char_x_pos = PADDING + (char_idx as u32) * (GLYPH_WIDTH + GLYPH_SPACING);
+
+            for (row_idx, row) in get_glyph(character).iter().enumerate() {
+                for bit_idx in 0..GLYPH_WIDTH {
+                    if (row >> (GLYPH_WIDTH - 1 - bit_idx)) & 1 == 1 {
+                        let x_pos = (char_x_pos + bit_idx) * pixel_size;
+                        let y_pos = (line_y_pos + row_idx as u32) * pixel_size;
+
+                        canvas.fill_rect(Rect::new(x_pos as i32, y_pos as i32, pixel_size, pixel_size)).unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
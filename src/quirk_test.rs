@@ -0,0 +1,141 @@
+use crate::assembler;
+use crate::chip8::{Chip8, Chip8Error};
+use crate::quirks::{IndexOnDumpLoad, Quirks};
+use crate::screen::SpriteWrap;
+
+/// address the memory increment probe stores its registers at (has to match [`SOURCE`])
+const SCRATCH_ADDRESS: u16 = 0x300;
+
+/// the probe program is much shorter, so this only guards against it getting stuck
+const MAX_INSTRUCTIONS: u64 = 1000;
+
+/// source of the probe program, which triggers each quirk once and leaves the results in the machine state
+///  - VA: result of the shift probe (1 if VY is shifted)
+///  - VB: VF after I overflowed from 0xFFF
+///  - screen: a sprite at the right edge (wraps to (0, 0)) and one starting at (66, 10) (wraps to (2, 10))
+///  - I: how far I moved after storing V0 and V1 at [`SCRATCH_ADDRESS`]
+const SOURCE: &str = "
+        # shift: VA = VB >> 1 (= 1) if VY is shifted, otherwise VA >> 1 (= 0)
+        LD VA, 0
+        LD VB, 2
+        SHR VA, VB
+
+        # index overflow
+        LD VF, 0
+        LD I, 0xFFF
+        LD V0, 1
+        ADD I, V0
+        LD VB, VF
+
+        # clipping: the upper row of glyph 0 (4 pixels) starting 2 pixels before the right edge
+        LD V0, 0
+        LD F, V0
+        LD V0, 62
+        LD V1, 0
+        DRW V0, V1, 1
+
+        # origin wrapping: the same row starting beyond the right edge
+        LD V0, 66
+        LD V1, 10
+        DRW V0, V1, 1
+
+        # memory increment
+        LD I, 0x300
+        LD [I], V1
+        HALT
+";
+
+/// the quirks an emulator configuration exhibits, as observed by running the probe program (see [`run()`])
+///
+/// **NOTE:** only the quirks this emulator can be configured for are reported (e.g. there is no VF reset probe)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuirkReport {
+    /// 8XY6 and 8XYE shift VY (instead of VX)
+    pub shift_uses_vy: bool,
+
+    /// FX1E sets VF when I overflows from 0xFFF
+    pub index_overflow_sets_flag: bool,
+
+    /// FX55 and FX65 move I
+    pub memory_increment: bool,
+
+    /// FX55 and FX65 move I by X (instead of X + 1); only meaningful if `memory_increment` is set
+    pub memory_increment_by_x: bool,
+
+    /// pixels of sprites beyond the edges of the screen wrap around (instead of being clipped)
+    pub sprite_pixels_wrap: bool,
+
+    /// sprites starting beyond the edges of the screen wrap around (instead of not being drawn)
+    pub sprite_origin_wraps: bool,
+}
+
+impl QuirkReport {
+    /// returns the names of the presets (see [`Quirks::PRESETS`]) that exhibit the same quirks
+    pub fn matching_presets(&self) -> Vec<&'static str> {
+        return Quirks::PRESETS.iter()
+            .filter(|(_, quirks)| QuirkReport::from(*quirks) == *self)
+            .map(|(name, _)| *name)
+            .collect();
+    }
+}
+
+/// the report the probe program is expected to produce for the given quirks
+impl From<Quirks> for QuirkReport {
+    fn from(quirks: Quirks) -> Self {
+        return QuirkReport {
+            shift_uses_vy: quirks.assign_before_shift,
+            index_overflow_sets_flag: quirks.set_flag_on_index_overflow,
+            memory_increment: quirks.index_on_dump_or_load != IndexOnDumpLoad::Unchanged,
+            memory_increment_by_x: quirks.index_on_dump_or_load == IndexOnDumpLoad::PlusX,
+            sprite_pixels_wrap: quirks.sprite_wrap == SpriteWrap::Always,
+            sprite_origin_wraps: quirks.sprite_wrap != SpriteWrap::Never,
+        };
+    }
+}
+
+/// returns the assembled probe program (see [`SOURCE`])
+pub fn program() -> Vec<u8> {
+    return assembler::assemble(SOURCE).expect("failed to assemble the quirk test");
+}
+
+/// runs the probe program with the given quirks and reports the observed behaviour
+pub fn run(quirks: Quirks) -> Result<QuirkReport, Chip8Error> {
+    let mut chip8 = Chip8::new(quirks);
+    chip8.load_program(&program())?;
+    chip8.run_instructions(MAX_INSTRUCTIONS)?;
+
+    let index_increment = chip8.get_index_reg().wrapping_sub(SCRATCH_ADDRESS);
+
+    return Ok(QuirkReport {
+        shift_uses_vy: chip8.get_register(0xA) == 1,
+        index_overflow_sets_flag: chip8.get_register(0xB) == 1,
+        memory_increment: index_increment != 0,
+        memory_increment_by_x: index_increment == 1,
+        sprite_pixels_wrap: chip8.frame().get_pixel(0, 0),
+        sprite_origin_wraps: chip8.frame().get_pixel(2, 10),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_matches_quirks() {
+        let mut configurations: Vec<Quirks> = Quirks::PRESETS.iter().map(|(_, quirks)| *quirks).collect();
+        configurations.push(Quirks::default());
+        configurations.push(Quirks { index_on_dump_or_load: IndexOnDumpLoad::PlusX, sprite_wrap: SpriteWrap::Never, ..Quirks::default() });
+
+        for quirks in configurations {
+            let report = run(quirks).expect("an error occurred during the quirk test");
+            assert_eq!(report, QuirkReport::from(quirks), "failed to report the quirks of {:?}", quirks);
+        }
+    }
+
+    #[test]
+    fn report_matching_presets() {
+        let report = run(Quirks::SUPER_CHIP).expect("an error occurred during the quirk test");
+
+        assert_eq!(report.matching_presets(), vec!("SUPER-CHIP"), "failed to match the SUPER-CHIP preset");
+    }
+}
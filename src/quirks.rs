@@ -0,0 +1,237 @@
+use serde::{Serialize, Deserialize};
+use crate::screen::SpriteWrap;
+
+/// behaviours in which the various CHIP-8 interpreters differ, so programs written for one of them might rely on it
+///
+/// **NOTE:** missing attributes fall back to their default values when deserializing (e.g. from an older config file)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Quirks {
+    /// specifies if the Y register is loaded into X before doing bit-shift operations or not
+    pub assign_before_shift: bool,
+
+    /// specifies whether it sets VF to 1 if I overflows from 0FFF to above 0x1000 (outside the normal addressing space) or not
+    pub set_flag_on_index_overflow: bool,
+
+    /// specifies how I is modified by the FX55 (reg_dump) and FX65 (reg_load) instructions (see [`IndexOnDumpLoad`])
+    pub index_on_dump_or_load: IndexOnDumpLoad,
+
+    /// specifies how sprites at the edges of the screen are drawn (see [`SpriteWrap`])
+    pub sprite_wrap: SpriteWrap,
+
+    /// specifies what happens on an instruction the emulator doesn't know (see [`UnknownOpcodePolicy`])
+    pub unknown_opcode_policy: UnknownOpcodePolicy,
+
+    /// specifies whether the 00CN (scroll down) instruction only scrolls by N/2 rows in lores mode, like the SUPER-CHIP
+    /// interpreter which always scrolls by hires rows (odd N are rounded down)
+    pub lores_scroll_half: bool,
+}
+
+impl Quirks {
+    /// the original interpreter of the COSMAC VIP
+    pub const COSMAC_VIP: Quirks = Quirks {
+        assign_before_shift: true,
+        set_flag_on_index_overflow: false,
+        index_on_dump_or_load: IndexOnDumpLoad::PlusXPlus1,
+        sprite_wrap: SpriteWrap::OriginOnly,
+        unknown_opcode_policy: UnknownOpcodePolicy::Error,
+        lores_scroll_half: false,
+    };
+
+    /// the SUPER-CHIP interpreter of the HP 48 calculators
+    pub const SUPER_CHIP: Quirks = Quirks {
+        assign_before_shift: false,
+        set_flag_on_index_overflow: false,
+        index_on_dump_or_load: IndexOnDumpLoad::Unchanged,
+        sprite_wrap: SpriteWrap::OriginOnly,
+        unknown_opcode_policy: UnknownOpcodePolicy::Error,
+        lores_scroll_half: true,
+    };
+
+    /// the XO-CHIP extension
+    pub const XO_CHIP: Quirks = Quirks {
+        assign_before_shift: true,
+        set_flag_on_index_overflow: false,
+        index_on_dump_or_load: IndexOnDumpLoad::PlusXPlus1,
+        sprite_wrap: SpriteWrap::Always,
+        unknown_opcode_policy: UnknownOpcodePolicy::Error,
+        lores_scroll_half: false,
+    };
+
+    /// the default settings of the Octo IDE (which follows XO-CHIP)
+    pub const OCTO: Quirks = Quirks::XO_CHIP;
+
+    /// the named presets (e.g. for letting the user pick an interpreter)
+    pub const PRESETS: [(&'static str, Quirks); 4] = [
+        ("COSMAC VIP", Quirks::COSMAC_VIP),
+        ("SUPER-CHIP", Quirks::SUPER_CHIP),
+        ("XO-CHIP", Quirks::XO_CHIP),
+        ("Octo", Quirks::OCTO),
+    ];
+
+    /// the short names of the presets, as used in the config file (see [`Quirks::from_profile()`])
+    pub const PROFILES: [(&'static str, Quirks); 4] = [
+        ("chip8", Quirks::COSMAC_VIP),
+        ("schip", Quirks::SUPER_CHIP),
+        ("xochip", Quirks::XO_CHIP),
+        ("octo", Quirks::OCTO),
+    ];
+
+    /// returns the preset with the given short name (case-insensitive; see [`Quirks::PROFILES`])
+    pub fn from_profile(name: &str) -> Option<Quirks> {
+        return Quirks::PROFILES.iter()
+            .find(|(profile, _)| profile.eq_ignore_ascii_case(name.trim()))
+            .map(|(_, quirks)| *quirks);
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        return Quirks {
+            assign_before_shift: true,
+            set_flag_on_index_overflow: true,
+            index_on_dump_or_load: IndexOnDumpLoad::Unchanged,
+            sprite_wrap: SpriteWrap::OriginOnly,
+            unknown_opcode_policy: UnknownOpcodePolicy::Error,
+            lores_scroll_half: false,
+        };
+    }
+}
+
+/// quirks that replace the ones of another configuration (e.g. for a single program); unset quirks are kept
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct QuirkOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assign_before_shift: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_flag_on_index_overflow: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_on_dump_or_load: Option<IndexOnDumpLoad>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sprite_wrap: Option<SpriteWrap>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_opcode_policy: Option<UnknownOpcodePolicy>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lores_scroll_half: Option<bool>,
+}
+
+impl QuirkOverrides {
+    /// returns the given quirks with the set overrides applied
+    pub fn apply_to(&self, quirks: Quirks) -> Quirks {
+        return Quirks {
+            assign_before_shift: self.assign_before_shift.unwrap_or(quirks.assign_before_shift),
+            set_flag_on_index_overflow: self.set_flag_on_index_overflow.unwrap_or(quirks.set_flag_on_index_overflow),
+            index_on_dump_or_load: self.index_on_dump_or_load.unwrap_or(quirks.index_on_dump_or_load),
+            sprite_wrap: self.sprite_wrap.unwrap_or(quirks.sprite_wrap),
+            unknown_opcode_policy: self.unknown_opcode_policy.unwrap_or(quirks.unknown_opcode_policy),
+            lores_scroll_half: self.lores_scroll_half.unwrap_or(quirks.lores_scroll_half),
+        };
+    }
+}
+
+/// specifies how I is modified after the registers V0 to VX were dumped to or loaded from memory
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum IndexOnDumpLoad {
+    /// `I = I + X + 1`, so I points behind the last accessed address (COSMAC VIP behaviour)
+    PlusXPlus1,
+
+    /// `I = I + X`, so I points to the last accessed address (CHIP-48 behaviour)
+    PlusX,
+
+    /// I keeps its value (SUPER-CHIP behaviour)
+    Unchanged,
+}
+
+impl Default for IndexOnDumpLoad {
+    fn default() -> Self {
+        return IndexOnDumpLoad::Unchanged;
+    }
+}
+
+/// specifies what happens when the emulator runs into an instruction it doesn't know (e.g. junk data of a program that
+/// relies on a lenient interpreter)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum UnknownOpcodePolicy {
+    /// stop with [`crate::chip8::Chip8Error::UnknownOpcode`]
+    Error,
+
+    /// skip the instruction and keep running
+    Ignore,
+
+    /// halt the emulator (like the `0x0000` instruction)
+    Halt,
+}
+
+impl Default for UnknownOpcodePolicy {
+    fn default() -> Self {
+        return UnknownOpcodePolicy::Error;
+    }
+}
+
+/// a program whose compatible quirks are known
+struct KnownRom {
+    /// CRC32 checksum of the program
+    checksum: u32,
+
+    quirks: Quirks,
+}
+
+/// programs with known quirks, identified by checksum (add an entry only after verifying the checksum of the actual file)
+const KNOWN_ROMS: [KnownRom; 1] = [
+    // programs/scroll.ch8 (scrolls by single lores rows with 00C2)
+    KnownRom { checksum: 0x32422CAE, quirks: Quirks::SUPER_CHIP },
+];
+
+/// returns the recommended quirks for known programs and `None` for unknown ones (use the default quirks in that case)
+pub fn detect_profile(rom: &[u8]) -> Option<Quirks> {
+    let checksum = rom_checksum(rom);
+
+    return KNOWN_ROMS.iter()
+        .find(|known_rom| known_rom.checksum == checksum)
+        .map(|known_rom| known_rom.quirks);
+}
+
+/// identifies a program by its CRC-32 (IEEE 802.3, as used by zip, png, etc.)
+pub fn rom_checksum(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFFFFFF;
+
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = if crc & 1 == 1 { 0xEDB88320 } else { 0 };
+            crc = (crc >> 1) ^ mask;
+        }
+    }
+
+    return !crc;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rom_checksum_check_value() {
+        assert_eq!(rom_checksum(b"123456789"), 0xCBF43926, "failed to compute the standard CRC-32 check value");
+    }
+
+    #[test]
+    fn detect_known_profile() {
+        let rom = include_bytes!("../programs/scroll.ch8");
+
+        let quirks = detect_profile(rom);
+        assert_eq!(quirks, Some(Quirks::SUPER_CHIP), "failed to detect the scroll program");
+        assert_ne!(quirks, Some(Quirks::default()), "failed to return the quirks of the program instead of the defaults");
+    }
+
+    #[test]
+    fn detect_unknown_profile() {
+        assert_eq!(detect_profile(&[0x12, 0x00]), None, "falsely detected an unknown program");
+    }
+}
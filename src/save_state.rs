@@ -0,0 +1,141 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+use crate::screen::FrameBuffer;
+
+/// identifies a blob as a save-state of this emulator
+#[cfg(feature = "std")]
+const MAGIC: [u8; 4] = *b"C8SS";
+
+/// has to be incremented whenever the layout of `SaveState` changes
+pub const VERSION: u16 = 3;
+
+/// magic (4 bytes) + version (2 bytes, little-endian)
+#[cfg(feature = "std")]
+const HEADER_SIZE: usize = 6;
+
+#[derive(Debug)]
+pub enum SaveStateError {
+    /// the data does not start with the save-state magic (e.g. it isn't a save-state at all)
+    BadHeader,
+
+    /// the save-state was created by an incompatible version of the emulator
+    VersionMismatch { expected: u16, found: u16 },
+
+    /// the header is fine, but the contents could not be deserialized
+    Corrupted(String),
+}
+
+/// a snapshot of the complete emulator state (see `Chip8::save_state()` and `Chip8::load_state()`)
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SaveState {
+    pub registers: [u8; 16],
+    pub program_counter: u16,
+    pub memory: Vec<u8>,
+    pub index_reg: u16,
+    pub stack_memory: Vec<u16>,
+    pub stack_pointer: u16,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub audio_pattern: Option<[u8; 16]>,
+    pub pitch: u8,
+    pub frame_buffer: FrameBuffer,
+    pub second_plane: FrameBuffer,
+    pub selected_planes: u8,
+    pub reached_end_of_file: bool,
+    pub halted: bool,
+}
+
+impl SaveState {
+    /// serializes the save-state with a leading header
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::with_capacity(HEADER_SIZE);
+        data.extend_from_slice(&MAGIC);
+        data.extend_from_slice(&VERSION.to_le_bytes());
+
+        // serializing into memory can't fail for plain data
+        let payload = bincode::serialize(self).expect("failed to serialize the save-state");
+        data.extend_from_slice(&payload);
+
+        return data;
+    }
+
+    /// checks the header before deserializing, so data from other sources or versions is rejected
+    #[cfg(feature = "std")]
+    pub fn from_bytes(data: &[u8]) -> Result<SaveState, SaveStateError> {
+        if data.len() < HEADER_SIZE || data[0..4] != MAGIC {
+            return Err(SaveStateError::BadHeader);
+        }
+
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != VERSION {
+            return Err(SaveStateError::VersionMismatch { expected: VERSION, found: version });
+        }
+
+        return bincode::deserialize(&data[HEADER_SIZE..])
+            .map_err(|err| SaveStateError::Corrupted(err.to_string()));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn create_save_state() -> SaveState {
+        return SaveState {
+            registers: [7; 16],
+            program_counter: 0x234,
+            memory: vec![0xAB; 0x1000],
+            index_reg: 0x300,
+            stack_memory: vec![0x202; 0x18],
+            stack_pointer: 1,
+            delay_timer: 30,
+            sound_timer: 10,
+            audio_pattern: Some([0xF0; 16]),
+            pitch: 64,
+            frame_buffer: FrameBuffer::default(),
+            second_plane: FrameBuffer::default(),
+            selected_planes: 0b01,
+            reached_end_of_file: false,
+            halted: false,
+        };
+    }
+
+    #[test]
+    fn round_trip() {
+        let save_state = create_save_state();
+
+        let restored = SaveState::from_bytes(&save_state.to_bytes()).expect("failed to restore the save-state");
+
+        assert_eq!(restored, save_state, "failed to round-trip the save-state");
+    }
+
+    #[test]
+    fn reject_bad_magic() {
+        let mut data = create_save_state().to_bytes();
+        data[0] = b'X';
+
+        assert!(matches!(SaveState::from_bytes(&data), Err(SaveStateError::BadHeader)), "failed to reject the corrupted magic");
+        assert!(matches!(SaveState::from_bytes(&data[0..3]), Err(SaveStateError::BadHeader)), "failed to reject the truncated header");
+    }
+
+    #[test]
+    fn reject_version_mismatch() {
+        let mut data = create_save_state().to_bytes();
+        data[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+
+        let result = SaveState::from_bytes(&data);
+
+        assert!(matches!(result, Err(SaveStateError::VersionMismatch { expected: VERSION, found }) if found == VERSION + 1), "failed to reject the other version");
+    }
+
+    #[test]
+    fn reject_truncated_payload() {
+        let data = create_save_state().to_bytes();
+
+        assert!(matches!(SaveState::from_bytes(&data[0..20]), Err(SaveStateError::Corrupted(_))), "failed to reject the truncated payload");
+    }
+}
@@ -1,41 +1,510 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+
 pub const HEIGHT: u32 = 32;
 pub const WIDTH: u32 = 64;
 
+/// RGBA colors of the 4 combinations of 2 bit-planes (XO-CHIP), indexed by `plane_1_bit | (plane_2_bit << 1)`
+///  - `0`: background; `1`: first plane only; `2`: second plane only; `3`: both planes
+pub type Palette = [[u8; 4]; 4];
+
+/// bit of the first plane in the plane selection (see [`Screen::select_planes()`])
+pub const FIRST_PLANE: u8 = 0b01;
+
+/// bit of the second plane in the plane selection (XO-CHIP)
+pub const SECOND_PLANE: u8 = 0b10;
+
+pub const ALL_PLANES: u8 = FIRST_PLANE | SECOND_PLANE;
+
+/// only the first plane is selected, so programs that don't know about planes behave as usual
+pub const DEFAULT_SELECTED_PLANES: u8 = FIRST_PLANE;
+
+/// a frame buffer that carries its own resolution, so renderers can query the dimensions instead of relying on
+/// [`WIDTH`] and [`HEIGHT`]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct FrameBuffer {
+    width: u32,
+    height: u32,
+
+    /// row-major pixel values (access using `pixels[y * width + x]`)
+    pixels: Vec<bool>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        return FrameBuffer {
+            width,
+            height,
+            pixels: vec![false; (width * height) as usize],
+        };
+    }
+
+    pub fn width(&self) -> u32 {
+        return self.width;
+    }
+
+    pub fn height(&self) -> u32 {
+        return self.height;
+    }
+
+    pub fn pixels(&self) -> &[bool] {
+        return &self.pixels;
+    }
+
+    /// returns `true` if the number of pixels matches the resolution (e.g. for checking a deserialized frame buffer)
+    pub fn is_consistent(&self) -> bool {
+        return (self.width as usize).checked_mul(self.height as usize) == Some(self.pixels.len());
+    }
+
+    /// **NOTE:** panics if the position lies outside of the frame buffer
+    pub fn get_pixel(&self, x_pos: u32, y_pos: u32) -> bool {
+        return self.pixels[self.pixel_index(x_pos, y_pos)];
+    }
+
+    /// **NOTE:** panics if the position lies outside of the frame buffer
+    pub fn set_pixel(&mut self, x_pos: u32, y_pos: u32, value: bool) {
+        let idx = self.pixel_index(x_pos, y_pos);
+        self.pixels[idx] = value;
+    }
+
+    pub fn clear(&mut self) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = false;
+        }
+    }
+
+    /// moves all pixels down by `rows` (the rows at the top are cleared, the ones moved past the bottom are lost)
+    pub fn scroll_down(&mut self, rows: u32) {
+        let row_len = self.width as usize;
+        let shift = (rows.min(self.height) as usize) * row_len;
+
+        self.pixels.rotate_right(shift);
+        for pixel in self.pixels[..shift].iter_mut() {
+            *pixel = false;
+        }
+    }
+
+    /// returns the pixels that changed since `previous` as `(index, new value)`, e.g. for sending only the changes to a
+    /// frontend
+    ///  - the index is the row-major position (`y * width + x`, like [`FrameBuffer::pixels()`]) and the entries are
+    ///    ordered by it
+    ///
+    /// **NOTE:** panics if the resolutions differ (send the whole frame buffer after a resolution change instead)
+    pub fn diff(&self, previous: &FrameBuffer) -> Vec<(u16, bool)> {
+        if previous.width != self.width || previous.height != self.height {
+            panic!("can't diff the {}x{} frame buffer against a {}x{} one!", self.width, self.height, previous.width, previous.height);
+        }
+
+        return self.pixels.iter()
+            .zip(previous.pixels.iter())
+            .enumerate()
+            .filter(|(_, (pixel, previous_pixel))| pixel != previous_pixel)
+            .map(|(idx, (pixel, _))| (idx as u16, *pixel))
+            .collect();
+    }
+
+    /// applies the changes returned by `diff()`, so that this frame buffer matches the one the diff was created from
+    ///
+    /// **NOTE:** panics if an index lies outside of the frame buffer
+    pub fn apply_diff(&mut self, diff: &[(u16, bool)]) {
+        for (idx, value) in diff {
+            self.pixels[*idx as usize] = *value;
+        }
+    }
+
+    /// renders the frame buffer as text (`#` = on; `.` = off), one line per row
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity(((self.width + 1) * self.height) as usize);
+
+        for y_pos in 0..self.height {
+            for x_pos in 0..self.width {
+                ascii.push(if self.get_pixel(x_pos, y_pos) { '#' } else { '.' });
+            }
+            ascii.push('\n');
+        }
+
+        return ascii;
+    }
+
+    /// renders the frame buffer as row-major RGBA bytes (4 bytes per pixel), e.g. for a canvas or texture upload
+    ///  - every pixel is replicated `scale` times in both directions, so the result is `width * scale` pixels wide
+    ///
+    /// **NOTE:** panics if `scale` is 0
+    pub fn to_rgba(&self, foreground_color: [u8; 4], background_color: [u8; 4], scale: u32) -> Vec<u8> {
+        let palette = [background_color, foreground_color, background_color, foreground_color];
+        return self.to_rgba_with_palette(None, &palette, scale);
+    }
+
+    /// like `to_rgba()`, but combines this frame buffer (bit 0) with an optional second bit-plane (bit 1) into an index of
+    /// the palette (see [`Palette`]); without a second plane only the first 2 colors are used
+    ///
+    /// **NOTE:** panics if `scale` is 0 or the second plane has another resolution
+    pub fn to_rgba_with_palette(&self, second_plane: Option<&FrameBuffer>, palette: &Palette, scale: u32) -> Vec<u8> {
+        if scale == 0 {
+            panic!("the scale of the RGBA frame buffer must be at least 1!");
+        }
+        if let Some(second_plane) = second_plane {
+            if second_plane.width != self.width || second_plane.height != self.height {
+                panic!("the {}x{} bit-plane doesn't match the {}x{} frame buffer!", second_plane.width, second_plane.height, self.width, self.height);
+            }
+        }
+
+        let scaled_width = (self.width * scale) as usize;
+        let mut rgba = Vec::with_capacity(scaled_width * (self.height * scale) as usize * 4);
+
+        for y_pos in 0..self.height {
+            let row_start = rgba.len();
+
+            for x_pos in 0..self.width {
+                let first_bit = self.get_pixel(x_pos, y_pos) as usize;
+                let second_bit = second_plane.map_or(false, |plane| plane.get_pixel(x_pos, y_pos)) as usize;
+                let color = &palette[(second_bit << 1) | first_bit];
+
+                for _ in 0..scale {
+                    rgba.extend_from_slice(color);
+                }
+            }
+
+            // replicate the row for the remaining scaled rows
+            for _ in 1..scale {
+                rgba.extend_from_within(row_start..(row_start + scaled_width * 4));
+            }
+        }
+
+        return rgba;
+    }
+
+    /// compatibility shim for renderers that still expect the fixed 64x32 layout (`[y][x]`)
+    ///  - pixels outside of the 64x32 area are dropped, missing pixels are `false`
+    pub fn to_lores_array(&self) -> [[bool; (WIDTH as usize)]; (HEIGHT as usize)] {
+        let mut array = [[false; (WIDTH as usize)]; (HEIGHT as usize)];
+
+        for y_pos in 0..self.height.min(HEIGHT) {
+            for x_pos in 0..self.width.min(WIDTH) {
+                array[y_pos as usize][x_pos as usize] = self.get_pixel(x_pos, y_pos);
+            }
+        }
+
+        return array;
+    }
+
+    fn pixel_index(&self, x_pos: u32, y_pos: u32) -> usize {
+        if x_pos >= self.width || y_pos >= self.height {
+            panic!("pixel ({}, {}) lies outside of the {}x{} frame buffer!", x_pos, y_pos, self.width, self.height);
+        }
+
+        return (y_pos * self.width + x_pos) as usize;
+    }
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        return FrameBuffer::new(WIDTH, HEIGHT);
+    }
+}
+
+/// specifies which value `display_sprite()` reports (and thus ends up in `VF`) when pixels are turned off
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CollisionMode {
+    /// `1` if any pixel was turned off, `0` otherwise
+    Binary,
+
+    /// number of sprite rows in which a pixel was turned off (used by some SCHIP interpreters)
+    RowCount,
+}
+
+impl Default for CollisionMode {
+    fn default() -> Self {
+        return CollisionMode::Binary;
+    }
+}
+
+/// specifies how `display_sprite()` combines the sprite with the pixels on the screen
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpriteDrawMode {
+    /// sprite pixels toggle the screen pixels (the real behaviour)
+    Xor,
+
+    /// sprite pixels are always turned on and never off, so no collisions are reported (for seeing which pixels sprites
+    /// cover when debugging)
+    Or,
+}
+
+impl Default for SpriteDrawMode {
+    fn default() -> Self {
+        return SpriteDrawMode::Xor;
+    }
+}
+
+/// specifies how `display_sprite()` handles sprites that don't fit on the screen
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum SpriteWrap {
+    /// the starting coordinate wraps around, but pixels beyond the edges are clipped (COSMAC VIP behaviour)
+    OriginOnly,
+
+    /// both the starting coordinate and the pixels beyond the edges wrap around to the other side
+    Always,
+
+    /// nothing wraps: pixels beyond the edges are clipped and sprites starting outside the screen aren't drawn at all
+    Never,
+}
+
+impl Default for SpriteWrap {
+    fn default() -> Self {
+        return SpriteWrap::OriginOnly;
+    }
+}
+
+/// a copy of the display contents (including the resolution), which is cheaper than a full save-state
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScreenSnapshot {
+    frame_buffer: FrameBuffer,
+    second_plane: FrameBuffer,
+    selected_planes: u8,
+}
+
+impl ScreenSnapshot {
+    pub fn frame(&self) -> &FrameBuffer {
+        return &self.frame_buffer;
+    }
+
+    pub fn second_plane(&self) -> &FrameBuffer {
+        return &self.second_plane;
+    }
+}
+
 pub struct Screen {
-    /// access pixel values using `pixel_vals[y][x]` (`x` = horizontal; `y` = vertical)
-    frame_buffer: [[bool; (WIDTH as usize)]; (HEIGHT as usize)],
+    /// the first bit-plane (the only one programs without XO-CHIP instructions draw on)
+    frame_buffer: FrameBuffer,
+
+    /// the second bit-plane (XO-CHIP)
+    second_plane: FrameBuffer,
+
+    /// bit mask of the planes that drawing and clearing affect (bit 0: first plane; bit 1: second plane)
+    selected_planes: u8,
+
+    collision_mode: CollisionMode,
+
+    sprite_wrap: SpriteWrap,
+
+    draw_mode: SpriteDrawMode,
+
+    /// specifies whether scrolling in lores mode moves by half the rows (see [`crate::quirks::Quirks::lores_scroll_half`])
+    lores_scroll_half: bool,
+
+    /// incremented whenever the contents (or the resolution) of the frame buffer change
+    version: u64,
 }
 
 impl Screen {
     pub fn new() -> Screen {
         return Screen {
-            frame_buffer: [[false; (WIDTH as usize)]; (HEIGHT as usize)],
+            frame_buffer: FrameBuffer::default(),
+            second_plane: FrameBuffer::default(),
+            selected_planes: DEFAULT_SELECTED_PLANES,
+            collision_mode: CollisionMode::default(),
+            sprite_wrap: SpriteWrap::default(),
+            draw_mode: SpriteDrawMode::default(),
+            lores_scroll_half: false,
+            version: 0,
         };
     }
 
-    pub fn get_frame_buffer(&self) -> &[[bool; (WIDTH as usize)]; (HEIGHT as usize)] {
+    pub fn set_collision_mode(&mut self, collision_mode: CollisionMode) {
+        self.collision_mode = collision_mode;
+    }
+
+    pub fn set_sprite_wrap(&mut self, sprite_wrap: SpriteWrap) {
+        self.sprite_wrap = sprite_wrap;
+    }
+
+    pub fn set_lores_scroll_half(&mut self, lores_scroll_half: bool) {
+        self.lores_scroll_half = lores_scroll_half;
+    }
+
+    pub fn set_draw_mode(&mut self, draw_mode: SpriteDrawMode) {
+        self.draw_mode = draw_mode;
+    }
+
+    pub fn get_draw_mode(&self) -> SpriteDrawMode {
+        return self.draw_mode;
+    }
+
+    /// selects the planes that `display_sprite()` and `clear()` affect (XO-CHIP `FN01`)
+    ///  - bit 0: first plane; bit 1: second plane (higher bits are ignored)
+    ///  - with no plane selected, nothing is drawn or cleared
+    pub fn select_planes(&mut self, planes: u8) {
+        self.selected_planes = planes & ALL_PLANES;
+    }
+
+    pub fn get_selected_planes(&self) -> u8 {
+        return self.selected_planes;
+    }
+
+    /// returns the first plane
+    pub fn get_frame_buffer(&self) -> &FrameBuffer {
         return &self.frame_buffer;
     }
 
-    /// returns `true` if a pixel was turned off in the process (set `VF` to `1`)
-    pub fn display_sprite(&mut self, x_pos: u8, y_pos: u8, sprite_data: &[u8]) -> bool {
-        let x_pos = x_pos % (WIDTH as u8);
-        let y_pos = y_pos % (HEIGHT as u8);
+    /// returns the second plane (XO-CHIP), which stays empty unless a program selects it (see `select_planes()`)
+    pub fn get_second_plane(&self) -> &FrameBuffer {
+        return &self.second_plane;
+    }
+
+    /// returns a number that only changes if the frame buffer changed (e.g. so a frontend can skip redrawing)
+    ///
+    /// **NOTE:** drawing that leaves every pixel as it was (e.g. clearing an empty screen) keeps the version
+    pub fn get_version(&self) -> u64 {
+        return self.version;
+    }
+
+    /// replaces the whole frame buffer (e.g. when loading a save-state)
+    pub fn set_frame_buffer(&mut self, frame_buffer: FrameBuffer) {
+        if frame_buffer != self.frame_buffer {
+            self.frame_buffer = frame_buffer;
+            self.version += 1;
+        }
+    }
+
+    /// replaces the whole second plane (e.g. when loading a save-state)
+    ///
+    /// **NOTE:** the resolution has to match the one of the first plane
+    pub fn set_second_plane(&mut self, second_plane: FrameBuffer) {
+        if second_plane != self.second_plane {
+            self.second_plane = second_plane;
+            self.version += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> ScreenSnapshot {
+        return ScreenSnapshot {
+            frame_buffer: self.frame_buffer.clone(),
+            second_plane: self.second_plane.clone(),
+            selected_planes: self.selected_planes,
+        };
+    }
+
+    /// restores the display contents and resolution of the snapshot
+    ///
+    /// **NOTE:** the frontend redraws the whole frame buffer every frame, so there is nothing else to update
+    pub fn restore(&mut self, snapshot: &ScreenSnapshot) {
+        self.set_frame_buffer(snapshot.frame_buffer.clone());
+        self.set_second_plane(snapshot.second_plane.clone());
+        self.selected_planes = snapshot.selected_planes;
+    }
+
+    /// changes the resolution of the screen (e.g. to 128x64 for hires mode), which also clears it
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.set_frame_buffer(FrameBuffer::new(width, height));
+        self.set_second_plane(FrameBuffer::new(width, height));
+    }
+
+    /// returns `true` if the resolution is higher than the default 64x32 (SUPER-CHIP hires mode)
+    pub fn is_hires(&self) -> bool {
+        return self.frame_buffer.width() > WIDTH;
+    }
+
+    /// scrolls the selected planes down by `amount` rows (SUPER-CHIP `00CN`)
+    ///
+    /// **NOTE:** in lores mode with the `lores_scroll_half` quirk, only `amount / 2` rows are scrolled (rounded down),
+    /// because the original interpreter scrolls by hires rows
+    pub fn scroll_down(&mut self, amount: u8) {
+        let rows = if self.lores_scroll_half && !self.is_hires() { amount / 2 } else { amount };
+        if rows == 0 {
+            return;
+        }
+
+        let mut changed = false;
+
+        if self.selected_planes & FIRST_PLANE != 0 && self.frame_buffer.pixels().iter().any(|pixel| *pixel) {
+            self.frame_buffer.scroll_down(rows as u32);
+            changed = true;
+        }
+
+        if self.selected_planes & SECOND_PLANE != 0 && self.second_plane.pixels().iter().any(|pixel| *pixel) {
+            self.second_plane.scroll_down(rows as u32);
+            changed = true;
+        }
+
+        if changed {
+            self.version += 1;
+        }
+    }
+
+    /// returns the collision value for `VF` depending on the collision mode (see [`CollisionMode`])
+    ///  - the sprite is drawn on each selected plane (see [`Screen::select_planes()`]); if both are selected, the first
+    ///    half of `sprite_data` is drawn on the first plane and the second half on the second plane
+    ///  - collisions on any of the planes count (with `CollisionMode::RowCount` the plane with the most collided rows)
+    pub fn display_sprite(&mut self, x_pos: u8, y_pos: u8, sprite_data: &[u8]) -> u8 {
+        let selected_planes = self.selected_planes;
+        let planes = [FIRST_PLANE, SECOND_PLANE].into_iter().filter(|plane| selected_planes & plane != 0);
+
+        let plane_count = planes.clone().count();
+        if plane_count == 0 {
+            return 0;
+        }
+
+        let rows_per_plane = sprite_data.len() / plane_count;
+        let mut collided_rows: u8 = 0;
+        let mut changed = false;
+
+        for (idx, plane) in planes.enumerate() {
+            let plane_data = &sprite_data[(idx * rows_per_plane)..((idx + 1) * rows_per_plane)];
+            let (plane_collided_rows, plane_changed) = self.draw_on_plane(plane, x_pos, y_pos, plane_data);
+
+            collided_rows = collided_rows.max(plane_collided_rows);
+            changed |= plane_changed;
+        }
+
+        if changed {
+            self.version += 1;
+        }
+
+        return match self.collision_mode {
+            CollisionMode::Binary => if collided_rows > 0 { 1 } else { 0 },
+            CollisionMode::RowCount => collided_rows,
+        };
+    }
 
-        let mut pixel_turned_off = false;
+    /// draws the sprite on a single plane and returns the number of rows in which a pixel was turned off and whether
+    /// any pixel changed
+    fn draw_on_plane(&mut self, plane: u8, x_pos: u8, y_pos: u8, sprite_data: &[u8]) -> (u8, bool) {
+        let sprite_wrap = self.sprite_wrap;
+        let draw_mode = self.draw_mode;
+        let frame_buffer = if plane == SECOND_PLANE { &mut self.second_plane } else { &mut self.frame_buffer };
+
+        let width = frame_buffer.width();
+        let height = frame_buffer.height();
+
+        let (x_pos, y_pos) = match sprite_wrap {
+            SpriteWrap::OriginOnly | SpriteWrap::Always => ((x_pos as u32) % width, (y_pos as u32) % height),
+            SpriteWrap::Never => (x_pos as u32, y_pos as u32),
+        };
+        let wrap_edges = sprite_wrap == SpriteWrap::Always;
+
+        let mut collided_rows: u8 = 0;
+        let mut changed = false;
 
         for (byte_idx, byte) in sprite_data.iter().enumerate() {
-            let curr_y = y_pos + (byte_idx as u8);
+            let mut curr_y = y_pos + (byte_idx as u32);
 
-            if curr_y >= (HEIGHT as u8) {
+            if wrap_edges {
+                curr_y %= height;
+            } else if curr_y >= height {
                 // this should achieve a clipping behaviour
                 continue;
             }
 
+            let mut pixel_turned_off = false;
+
             for bit_idx in 0..8 {
-                let curr_x = x_pos + bit_idx;
+                let mut curr_x = x_pos + bit_idx;
 
-                if curr_x >= (WIDTH as u8) {
+                if wrap_edges {
+                    curr_x %= width;
+                } else if curr_x >= width {
                     // this should achieve a clipping behaviour
                     continue;
                 }
@@ -44,22 +513,183 @@ impl Screen {
                 let bit: bool = ((byte >> (7 - bit_idx)) & 1) == 1;
 
                 if bit {
-                    let curr_val = self.frame_buffer[curr_y as usize][curr_x as usize];
-                    self.frame_buffer[curr_y as usize][curr_x as usize] = !curr_val;
+                    let curr_val = frame_buffer.get_pixel(curr_x, curr_y);
 
-                    pixel_turned_off |= curr_val;
+                    match draw_mode {
+                        SpriteDrawMode::Xor => {
+                            frame_buffer.set_pixel(curr_x, curr_y, !curr_val);
+                            pixel_turned_off |= curr_val;
+                            changed = true;
+                        },
+                        SpriteDrawMode::Or => {
+                            frame_buffer.set_pixel(curr_x, curr_y, true);
+                            changed |= !curr_val;
+                        },
+                    }
                 }
             }
+
+            if pixel_turned_off {
+                collided_rows += 1;
+            }
         }
 
-        return pixel_turned_off;
+        return (collided_rows, changed);
     }
 
+    /// clears both planes and selects only the first one again (e.g. when the emulator is reset)
+    pub fn reset(&mut self) {
+        self.selected_planes = ALL_PLANES;
+        self.clear();
+        self.selected_planes = DEFAULT_SELECTED_PLANES;
+    }
+
+    /// clears the selected planes (see [`Screen::select_planes()`])
     pub fn clear(&mut self) {
-        for y in 0..(HEIGHT as usize) {
-            for x in 0..(WIDTH as usize) {
-                self.frame_buffer[y][x] = false;
-            }
+        let mut changed = false;
+
+        if self.selected_planes & FIRST_PLANE != 0 && self.frame_buffer.pixels().iter().any(|pixel| *pixel) {
+            self.frame_buffer.clear();
+            changed = true;
+        }
+
+        if self.selected_planes & SECOND_PLANE != 0 && self.second_plane.pixels().iter().any(|pixel| *pixel) {
+            self.second_plane.clear();
+            changed = true;
+        }
+
+        if changed {
+            self.version += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOREGROUND_COLOR: [u8; 4] = [0xFF, 0xFE, 0xFD, 0xFC];
+    const BACKGROUND_COLOR: [u8; 4] = [0x01, 0x02, 0x03, 0x04];
+
+    /// 2x2 frame buffer with the pixels (0, 0) and (1, 1) turned on
+    fn init_diagonal_frame_buffer() -> FrameBuffer {
+        let mut frame_buffer = FrameBuffer::new(2, 2);
+        frame_buffer.set_pixel(0, 0, true);
+        frame_buffer.set_pixel(1, 1, true);
+
+        return frame_buffer;
+    }
+
+    #[test]
+    fn frame_buffer_to_rgba() {
+        let rgba = init_diagonal_frame_buffer().to_rgba(FOREGROUND_COLOR, BACKGROUND_COLOR, 1);
+
+        let expected = [FOREGROUND_COLOR, BACKGROUND_COLOR, BACKGROUND_COLOR, FOREGROUND_COLOR].concat();
+        assert_eq!(rgba, expected, "failed to correctly convert the frame buffer to RGBA");
+    }
+
+    #[test]
+    fn frame_buffer_to_scaled_rgba() {
+        let rgba = init_diagonal_frame_buffer().to_rgba(FOREGROUND_COLOR, BACKGROUND_COLOR, 2);
+
+        let top_row = [FOREGROUND_COLOR, FOREGROUND_COLOR, BACKGROUND_COLOR, BACKGROUND_COLOR].concat();
+        let bottom_row = [BACKGROUND_COLOR, BACKGROUND_COLOR, FOREGROUND_COLOR, FOREGROUND_COLOR].concat();
+        let expected = [top_row.clone(), top_row, bottom_row.clone(), bottom_row].concat();
+        assert_eq!(rgba, expected, "failed to correctly replicate the pixels when scaling the RGBA frame buffer");
+
+        let hires_rgba = FrameBuffer::new(128, 64).to_rgba(FOREGROUND_COLOR, BACKGROUND_COLOR, 3);
+        assert_eq!(hires_rgba.len(), 128 * 3 * 64 * 3 * 4, "failed to correctly scale the hires frame buffer");
+    }
+
+    #[test]
+    fn diff_frame_buffers() {
+        let previous = init_diagonal_frame_buffer();
+        assert!(previous.diff(&previous).is_empty(), "failed to return an empty diff for an unchanged frame buffer");
+
+        let mut current = previous.clone();
+        current.set_pixel(0, 1, true);
+        assert_eq!(current.diff(&previous), vec!((2, true)), "failed to return the single changed pixel");
+
+        current.set_pixel(0, 0, false);
+        let diff = current.diff(&previous);
+        assert_eq!(diff, vec!((0, false), (2, true)), "failed to order the changed pixels by index");
+
+        let mut reconstructed = previous.clone();
+        reconstructed.apply_diff(&diff);
+        assert_eq!(reconstructed, current, "failed to reconstruct the frame buffer from the diff");
+    }
+
+    #[test]
+    fn two_planes_to_rgba() {
+        let palette: Palette = [[0, 0, 0, 255], [1, 1, 1, 255], [2, 2, 2, 255], [3, 3, 3, 255]];
+
+        // (0, 0): no plane; (1, 0): first plane; (0, 1): second plane; (1, 1): both planes
+        let mut first_plane = FrameBuffer::new(2, 2);
+        first_plane.set_pixel(1, 0, true);
+        first_plane.set_pixel(1, 1, true);
+
+        let mut second_plane = FrameBuffer::new(2, 2);
+        second_plane.set_pixel(0, 1, true);
+        second_plane.set_pixel(1, 1, true);
+
+        let rgba = first_plane.to_rgba_with_palette(Some(&second_plane), &palette, 1);
+        assert_eq!(rgba, palette.concat(), "failed to pick the palette colors by the bit-planes");
+
+        let single_plane_rgba = first_plane.to_rgba_with_palette(None, &palette, 1);
+        let expected = [palette[0], palette[1], palette[0], palette[1]].concat();
+        assert_eq!(single_plane_rgba, expected, "failed to only use the first 2 colors for a single plane");
+    }
+
+    #[test]
+    fn draw_on_selected_planes() {
+        let mut screen = Screen::new();
+
+        // only the second plane is drawn on
+        screen.select_planes(SECOND_PLANE);
+        screen.display_sprite(0, 0, &[0x80]);
+        assert!(screen.get_second_plane().get_pixel(0, 0), "failed to draw on the second plane");
+        assert!(!screen.get_frame_buffer().get_pixel(0, 0), "falsely drew on the first plane");
+
+        // both planes: the first byte is drawn on the first plane and the second byte on the second plane
+        screen.select_planes(ALL_PLANES);
+        let collision = screen.display_sprite(0, 1, &[0x80, 0x40]);
+        assert!(screen.get_frame_buffer().get_pixel(0, 1) && !screen.get_frame_buffer().get_pixel(1, 1), "failed to draw the first half of the sprite on the first plane");
+        assert!(screen.get_second_plane().get_pixel(1, 1) && !screen.get_second_plane().get_pixel(0, 1), "failed to draw the second half of the sprite on the second plane");
+        assert_eq!(collision, 0, "falsely reported a collision");
+
+        // clearing only affects the selected planes
+        screen.select_planes(FIRST_PLANE);
+        screen.clear();
+        assert!(!screen.get_frame_buffer().get_pixel(0, 1), "failed to clear the first plane");
+        assert!(screen.get_second_plane().get_pixel(0, 0), "falsely cleared the second plane");
+    }
+
+    /// scrolls a screen with the pixel (0, 0) turned on down by `amount` and returns the row the pixel ended up in
+    fn scroll_top_pixel(hires: bool, lores_scroll_half: bool, amount: u8) -> Option<u32> {
+        let mut screen = Screen::new();
+        if hires {
+            screen.resize(128, 64);
         }
+        screen.set_lores_scroll_half(lores_scroll_half);
+
+        screen.display_sprite(0, 0, &[0x80]);
+        screen.scroll_down(amount);
+
+        let frame_buffer = screen.get_frame_buffer();
+        return (0..frame_buffer.height()).find(|&y_pos| frame_buffer.get_pixel(0, y_pos));
+    }
+
+    #[test]
+    fn scroll_down_lores() {
+        assert_eq!(scroll_top_pixel(false, false, 4), Some(4), "failed to scroll by full rows without the quirk");
+        assert_eq!(scroll_top_pixel(false, true, 4), Some(2), "failed to scroll by half the rows with the quirk");
+        assert_eq!(scroll_top_pixel(false, true, 5), Some(2), "failed to round down half the rows");
+        assert_eq!(scroll_top_pixel(false, false, 40), None, "failed to drop the pixels scrolled past the bottom");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn scroll_down_hires() {
+        assert_eq!(scroll_top_pixel(true, false, 4), Some(4), "failed to scroll by full rows");
+        assert_eq!(scroll_top_pixel(true, true, 5), Some(5), "falsely scrolled by half the rows in hires mode");
+    }
+}
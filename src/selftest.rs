@@ -0,0 +1,95 @@
+use crate::assembler;
+
+/// source of the built-in diagnostic program, which checks the display and audio setup without any ROM
+///  1. fills the screen with a checkerboard and beeps for half a second
+///  2. draws the glyphs of the font (0 - F) one after another
+///  3. starts over (until the emulator is closed)
+const SOURCE: &str = "
+    : start
+        CLS
+        LD I, checker
+        LD V1, 0
+    : checker_row
+        LD V0, 0
+    : checker_tile
+        DRW V0, V1, 8
+        ADD V0, 8
+        SE V0, 64
+        JP checker_tile
+        ADD V1, 8
+        SE V1, 32
+        JP checker_row
+
+        # beep for half a second and keep the checkerboard for 2 seconds
+        LD V2, 30
+        LD ST, V2
+        LD V2, 120
+        CALL wait
+
+        # draw the glyphs in 2 rows of 8 (V3 = glyph; V0, V1 = position)
+        CLS
+        LD V3, 0
+        LD V0, 4
+        LD V1, 6
+    : glyph
+        LD F, V3
+        DRW V0, V1, 5
+        LD V2, 15
+        CALL wait
+        ADD V3, 1
+        ADD V0, 7
+        SE V3, 8
+        JP next_glyph
+        LD V0, 4
+        LD V1, 20
+    : next_glyph
+        SE V3, 16
+        JP glyph
+
+        LD V2, 120
+        CALL wait
+        JP start
+
+    # waits for V2 frames
+    : wait
+        LD DT, V2
+    : wait_loop
+        LD V2, DT
+        SE V2, 0
+        JP wait_loop
+        RET
+
+    : checker
+        DB 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55
+";
+
+/// returns the assembled diagnostic program (see [`SOURCE`])
+pub fn program() -> Vec<u8> {
+    return assembler::assemble(SOURCE).expect("failed to assemble the built-in self test");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip8::Chip8;
+    use crate::quirks::Quirks;
+
+    #[test]
+    fn run_selftest() {
+        let mut chip8 = Chip8::new(Quirks::default());
+        chip8.set_cycles_per_frame(200);
+        chip8.load_program(&program()).expect("failed to load the self test");
+
+        for _ in 0..2 {
+            chip8.run_frame(std::time::Duration::ZERO).expect("an error occurred during the self test");
+        }
+
+        // verify result
+        let frame = chip8.frame();
+        for (x_pos, y_pos) in [(0, 0), (63, 31), (10, 20)] {
+            let expected = (x_pos + y_pos) % 2 == 0;
+            assert_eq!(frame.get_pixel(x_pos, y_pos), expected, "failed to draw the checkerboard at ({}, {})", x_pos, y_pos);
+        }
+        assert!(chip8.playing_sound(), "failed to play the beep");
+    }
+}
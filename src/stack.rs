@@ -1,34 +1,110 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// number of return addresses the stack holds if no other depth is configured
+pub const DEFAULT_DEPTH: usize = 0x18;
+
+/// returned by `Stack::push()` if the stack is already filled up to its capacity
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StackOverflow;
+
 /// **NOTE:** the stack is only used for storing return addresses when calling subroutines
 pub struct Stack {
-    // 48 bytes of stack memory (24 x 2 bytes)
-    pub memory: [u16; 0x18],
+    // 2 bytes of stack memory per return address (e.g. 48 bytes for the default depth of 24)
+    pub memory: Vec<u16>,
 
     pub stack_pointer: u16,
 }
 
 impl Stack {
-    pub fn new() -> Stack {
+    /// creates an empty stack that holds up to `capacity` return addresses (e.g. 12 for the COSMAC VIP or 16 for SUPER-CHIP)
+    pub fn new(capacity: usize) -> Stack {
         return Stack {
-            memory: [0; 0x18],
+            memory: vec![0; capacity],
             stack_pointer: 0,
         };
     }
 
-    pub fn pop(&mut self) -> u16 {
-        if self.stack_pointer <= 0 {
-            panic!("stack underflow!");
+    /// returns `None` on a stack underflow
+    pub fn pop(&mut self) -> Option<u16> {
+        if self.stack_pointer == 0 {
+            return None;
         }
 
         self.stack_pointer -= 1;
-        return self.memory[self.stack_pointer as usize];
+        return Some(self.memory[self.stack_pointer as usize]);
     }
 
-    pub fn push(&mut self, return_address: u16) {
+    /// returns `Err(StackOverflow)` on a stack overflow
+    pub fn push(&mut self, return_address: u16) -> Result<(), StackOverflow> {
         if (self.stack_pointer as usize) >= self.memory.len() {
-            panic!("stack overflow!");
+            return Err(StackOverflow);
         }
 
         self.memory[self.stack_pointer as usize] = return_address;
         self.stack_pointer += 1;
+
+        return Ok(());
+    }
+
+    /// returns the number of return addresses currently on the stack
+    pub fn depth(&self) -> usize {
+        return self.stack_pointer as usize;
+    }
+
+    /// returns the return address on top of the stack without popping it (`None` if the stack is empty)
+    pub fn peek(&self) -> Option<u16> {
+        return self.as_slice().last().copied();
+    }
+
+    /// returns the return addresses currently on the stack, from the bottom (first call) to the top (last call)
+    pub fn as_slice(&self) -> &[u16] {
+        return &self.memory[..self.depth()];
+    }
+
+    pub fn capacity(&self) -> usize {
+        return self.memory.len();
+    }
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        return Stack::new(DEFAULT_DEPTH);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_configured_depth() {
+        let mut stack = Stack::new(2);
+
+        assert!(stack.push(0x200).is_ok(), "failed to push the first return address");
+        assert!(stack.push(0x202).is_ok(), "failed to push the second return address");
+        assert_eq!(stack.push(0x204), Err(StackOverflow), "failed to report the stack overflow");
+        assert_eq!(stack.depth(), 2, "failed to keep the depth at the capacity");
+
+        assert_eq!(stack.pop(), Some(0x202), "failed to pop the last return address");
+    }
+
+    #[test]
+    fn inspect_without_popping() {
+        let mut stack = Stack::default();
+        assert_eq!(stack.peek(), None, "failed to report the empty stack");
+
+        for return_address in [0x202, 0x304, 0x406] {
+            stack.push(return_address).expect("failed to push the return address");
+        }
+
+        for _ in 0..2 {
+            assert_eq!(stack.peek(), Some(0x406), "failed to return the top of the stack");
+            assert_eq!(stack.depth(), 3, "failed to return the depth");
+            assert_eq!(stack.as_slice(), &[0x202, 0x304, 0x406], "failed to return the entries on the stack");
+        }
+
+        stack.pop();
+        assert_eq!(stack.as_slice(), &[0x202, 0x304], "failed to exclude the popped entry");
+    }
+}